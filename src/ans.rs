@@ -1,5 +1,5 @@
 use crate::std_rng;
-use crate::cng;
+use crate::ans_domain::AnsDomain;
 
 
 pub fn i32_in_range(r: (i32,i32),i:i32) -> bool {
@@ -54,23 +54,19 @@ the questions of a QStruct.
 
 For an RNBNode with its objective o in [0,1,2], and its known answer x for q, 
 the AnsBox will provide RNBNode with an answer that it then uses to respond
-back to the QStruct.
+back to the QStruct. Answers are perturbed by the answer domain of q.
 
 For objective 2, uses functions in std_rng
 */ 
+#[derive(Clone)]
 pub struct Ansbox {
 }
 
 impl Ansbox {
 
-    /// outputs a value in ansrange based on known answer and objective
-    pub fn obj_ans(&mut self, ansrange:(i32,i32),known_ans:Option<i32>,obj:usize) -> i32 {
-        let mut ka:i32 = 0;
-        if !known_ans.is_none() {
-            ka = known_ans.unwrap();
-        } else {
-            ka = ansrange.0 + ((ansrange.1 - ansrange.0) as f32 / 2.0).round() as i32;
-        }
+    /// outputs a value in domain `dom` based on known answer and objective
+    pub fn obj_ans<D:AnsDomain>(&mut self, dom:&D,known_ans:Option<D::Ans>,obj:usize) -> D::Ans {
+        let ka = if !known_ans.is_none() {known_ans.unwrap()} else {dom.midpoint()};
 
         // no deception
         let mut i:f32 = 0.;
@@ -84,7 +80,6 @@ impl Ansbox {
             i = std_rng::random_f32_in_range((0.5,1.));
         }
 
-        calculate_ans(ansrange,ka,i)
+        dom.perturb(&ka,i)
     }
 }
-
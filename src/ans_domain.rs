@@ -0,0 +1,574 @@
+//! answer domains of questions.
+//! A question of Q has an answer domain that supplies the operations
+//! used by the question, answer and contradiction pipeline. The integer
+//! range (i32,i32) is the original domain.
+use crate::ans;
+use crate::std_rng;
+use std::fmt;
+
+/// operations on the answers of a question
+pub trait AnsDomain: Clone + fmt::Debug {
+    type Ans: Clone + PartialEq + Default + fmt::Debug + 'static;
+
+    /// if answer `a` is in domain
+    fn contains(&self,a:&Self::Ans) -> bool;
+
+    /// distance between answers, on the scale of the domain
+    fn distance(&self,a:&Self::Ans,b:&Self::Ans) -> f32;
+
+    /// maximum distance of any answer in domain from answer `a`
+    fn max_distance_from(&self,a:&Self::Ans) -> f32;
+
+    /// distance between the two answers furthest apart
+    fn diameter(&self) -> f32;
+
+    /// default answer used when the known answer is missing
+    fn midpoint(&self) -> Self::Ans;
+
+    /// # description
+    /// perturbs known answer `a` by the degree of deception `dec_degree`
+    /// in [0,1]. Degree 0 outputs `a`.
+    fn perturb(&self,a:&Self::Ans,dec_degree:f32) -> Self::Ans;
+
+    /// nearest answer in domain to `a`
+    fn nearest(&self,a:&Self::Ans) -> Self::Ans;
+
+    /// random answer in domain
+    fn random(&self) -> Self::Ans;
+
+    /// weighted mean of answers
+    fn mean(&self,v:&Vec<(Self::Ans,f32)>) -> Self::Ans;
+
+    /// integer view of an answer, used by integer sequence encoders
+    fn as_i32(&self,_a:&Self::Ans) -> Option<i32> {
+        None
+    }
+
+    /// answer of an integer view
+    fn from_i32(&self,_i:i32) -> Option<Self::Ans> {
+        None
+    }
+
+    /// distance scaled by the domain diameter into [0,1]
+    fn normalize(&self,d:f32) -> f32 {
+        let x = self.diameter();
+        if x == 0. {
+            return 0.;
+        }
+        d / x
+    }
+
+    /// # description
+    /// contradiction of `actual` against `known`: distance relative to the
+    /// maximum possible distance from `known`.
+    fn contradiction(&self,known:&Self::Ans,actual:&Self::Ans) -> f32 {
+        assert!(self.contains(known),"known {:?} not in {:?}",known,self);
+        assert!(self.contains(actual),"actual {:?} not in {:?}",actual,self);
+        let d = self.max_distance_from(known);
+        if d == 0. {
+            return 0.;
+        }
+        self.distance(known,actual) / d
+    }
+
+    /// running mean of `n` answers with mean `m` after adding answer `a`
+    fn update_mean(&self,m:&Self::Ans,n:usize,a:&Self::Ans) -> Self::Ans {
+        if n == 0 {
+            return a.clone();
+        }
+        self.mean(&vec![(m.clone(),n as f32),(a.clone(),1.)])
+    }
+}
+
+/// integer range
+impl AnsDomain for (i32,i32) {
+    type Ans = i32;
+
+    fn contains(&self,a:&i32) -> bool {
+        ans::i32_in_range(*self,*a)
+    }
+
+    fn distance(&self,a:&i32,b:&i32) -> f32 {
+        (*a as i64 - *b as i64).abs() as f32
+    }
+
+    fn max_distance_from(&self,a:&i32) -> f32 {
+        (*a as i64 - self.0 as i64).max(self.1 as i64 - *a as i64) as f32
+    }
+
+    fn diameter(&self) -> f32 {
+        (self.1 as i64 - self.0 as i64) as f32
+    }
+
+    fn midpoint(&self) -> i32 {
+        self.0 + ((self.1 - self.0) as f32 / 2.0).round() as i32
+    }
+
+    fn perturb(&self,a:&i32,dec_degree:f32) -> i32 {
+        ans::calculate_ans(*self,*a,dec_degree)
+    }
+
+    fn nearest(&self,a:&i32) -> i32 {
+        (*a).clamp(self.0,self.1)
+    }
+
+    fn random(&self) -> i32 {
+        std_rng::random_i32_in_range(*self)
+    }
+
+    fn mean(&self,v:&Vec<(i32,f32)>) -> i32 {
+        let s:f32 = v.iter().map(|x| x.1).sum();
+        // case: no answer, rounded center of the range
+        if s == 0. {
+            return ((self.0 + self.1) as f32 / 2.).round() as i32;
+        }
+        (v.iter().map(|x| x.0 as f32 * x.1).sum::<f32>() / s).round() as i32
+    }
+
+    fn as_i32(&self,a:&i32) -> Option<i32> {
+        Some(*a)
+    }
+
+    fn from_i32(&self,i:i32) -> Option<i32> {
+        if !self.contains(&i) {
+            return None;
+        }
+        Some(i)
+    }
+
+    fn contradiction(&self,known:&i32,actual:&i32) -> f32 {
+        ans::invert_calculate_ans(*self,*known,*actual)
+    }
+
+    /// running mean of QData.z
+    fn update_mean(&self,m:&i32,n:usize,a:&i32) -> i32 {
+        ((*m as f32 * n as f32 + *a as f32) / ((n + 1) as f32).round()) as i32
+    }
+}
+
+/// real range; a probability is RealRange(0.,1.)
+#[derive(Clone,Debug,PartialEq)]
+pub struct RealRange(pub f64,pub f64);
+
+impl AnsDomain for RealRange {
+    type Ans = f64;
+
+    fn contains(&self,a:&f64) -> bool {
+        *a >= self.0 && *a <= self.1
+    }
+
+    fn distance(&self,a:&f64,b:&f64) -> f32 {
+        (a - b).abs() as f32
+    }
+
+    fn max_distance_from(&self,a:&f64) -> f32 {
+        (a - self.0).max(self.1 - a) as f32
+    }
+
+    fn diameter(&self) -> f32 {
+        (self.1 - self.0) as f32
+    }
+
+    fn midpoint(&self) -> f64 {
+        (self.0 + self.1) / 2.
+    }
+
+    fn perturb(&self,a:&f64,dec_degree:f32) -> f64 {
+        assert!(self.contains(a),"known {} not in {:?}",a,self);
+        if dec_degree == 0. {
+            return *a;
+        }
+
+        // candidates within rounding error of the range are clamped into it
+        let dx = dec_degree as f64 * (a - self.0).max(self.1 - a);
+        let e = 1e-9 * (self.1 - self.0).max(1.);
+        let s:Vec<f64> = vec![a - dx,a + dx].into_iter().filter(|x| *x >= self.0 - e && *x <= self.1 + e)
+            .map(|x| self.nearest(&x)).collect();
+        let i = std_rng::random_i32_in_range((0,s.len() as i32 - 1));
+        s[i as usize]
+    }
+
+    fn nearest(&self,a:&f64) -> f64 {
+        a.clamp(self.0,self.1)
+    }
+
+    fn random(&self) -> f64 {
+        std_rng::random_f32_in_range((self.0 as f32,self.1 as f32)) as f64
+    }
+
+    fn mean(&self,v:&Vec<(f64,f32)>) -> f64 {
+        let s:f32 = v.iter().map(|x| x.1).sum();
+        if s == 0. {
+            return self.midpoint();
+        }
+        v.iter().map(|x| x.0 * x.1 as f64).sum::<f64>() / s as f64
+    }
+}
+
+/// categorical choice; an answer is the index of its label
+#[derive(Clone,Debug,PartialEq)]
+pub struct Categorical {
+    pub labels: Vec<String>
+}
+
+pub fn build_Categorical(labels:Vec<&str>) -> Categorical {
+    assert!(labels.len() > 0);
+    Categorical{labels:labels.into_iter().map(|x| x.to_string()).collect()}
+}
+
+impl AnsDomain for Categorical {
+    type Ans = usize;
+
+    fn contains(&self,a:&usize) -> bool {
+        *a < self.labels.len()
+    }
+
+    fn distance(&self,a:&usize,b:&usize) -> f32 {
+        if a == b {0.} else {1.}
+    }
+
+    fn max_distance_from(&self,_a:&usize) -> f32 {
+        self.diameter()
+    }
+
+    fn diameter(&self) -> f32 {
+        if self.labels.len() > 1 {1.} else {0.}
+    }
+
+    fn midpoint(&self) -> usize {
+        self.labels.len() / 2
+    }
+
+    /// with probability `dec_degree`, a random other label
+    fn perturb(&self,a:&usize,dec_degree:f32) -> usize {
+        assert!(self.contains(a),"known {} not in {:?}",a,self);
+        let l = self.labels.len();
+        if dec_degree == 0. || l == 1 {
+            return *a;
+        }
+
+        if std_rng::random_f32_in_range((0.,1.)) >= dec_degree {
+            return *a;
+        }
+        let i = std_rng::random_i32_in_range((0,l as i32 - 2)) as usize;
+        if i >= *a {i + 1} else {i}
+    }
+
+    fn nearest(&self,a:&usize) -> usize {
+        (*a).min(self.labels.len() - 1)
+    }
+
+    fn random(&self) -> usize {
+        std_rng::random_i32_in_range((0,self.labels.len() as i32 - 1)) as usize
+    }
+
+    /// weighted plurality; ties go to the smallest index
+    fn mean(&self,v:&Vec<(usize,f32)>) -> usize {
+        let mut c:Vec<f32> = vec![0.;self.labels.len()];
+        // case: label out of range has no share
+        for x in v.iter().filter(|x| self.contains(&x.0)) {
+            c[x.0] += x.1;
+        }
+        if c.iter().sum::<f32>() == 0. {
+            return self.midpoint();
+        }
+        c.into_iter().enumerate().fold((0,f32::MIN),|x,x2| if x2.1 > x.1 {x2} else {x}).0
+    }
+}
+
+/// box of real ranges; an answer is a small vector
+#[derive(Clone,Debug,PartialEq)]
+pub struct VecRange {
+    pub lo: Vec<f64>,
+    pub hi: Vec<f64>
+}
+
+pub fn build_VecRange(lo:Vec<f64>,hi:Vec<f64>) -> VecRange {
+    assert!(lo.len() == hi.len() && lo.len() > 0);
+    VecRange{lo:lo,hi:hi}
+}
+
+impl VecRange {
+
+    fn component(&self,i:usize) -> RealRange {
+        RealRange(self.lo[i],self.hi[i])
+    }
+}
+
+impl AnsDomain for VecRange {
+    type Ans = Vec<f64>;
+
+    fn contains(&self,a:&Vec<f64>) -> bool {
+        a.len() == self.lo.len() && (0..a.len()).all(|i| self.component(i).contains(&a[i]))
+    }
+
+    /// euclidean distance
+    fn distance(&self,a:&Vec<f64>,b:&Vec<f64>) -> f32 {
+        a.iter().zip(b.iter()).map(|(x,y)| (x - y) * (x - y)).sum::<f64>().sqrt() as f32
+    }
+
+    fn max_distance_from(&self,a:&Vec<f64>) -> f32 {
+        (0..a.len()).map(|i| (self.component(i).max_distance_from(&a[i]) as f64).powi(2))
+            .sum::<f64>().sqrt() as f32
+    }
+
+    fn diameter(&self) -> f32 {
+        self.distance(&self.lo,&self.hi)
+    }
+
+    fn midpoint(&self) -> Vec<f64> {
+        (0..self.lo.len()).map(|i| self.component(i).midpoint()).collect()
+    }
+
+    /// perturbs each component by `dec_degree`
+    fn perturb(&self,a:&Vec<f64>,dec_degree:f32) -> Vec<f64> {
+        (0..a.len()).map(|i| self.component(i).perturb(&a[i],dec_degree)).collect()
+    }
+
+    fn nearest(&self,a:&Vec<f64>) -> Vec<f64> {
+        if a.len() != self.lo.len() {
+            return self.midpoint();
+        }
+        (0..a.len()).map(|i| self.component(i).nearest(&a[i])).collect()
+    }
+
+    fn random(&self) -> Vec<f64> {
+        (0..self.lo.len()).map(|i| self.component(i).random()).collect()
+    }
+
+    fn mean(&self,v:&Vec<(Vec<f64>,f32)>) -> Vec<f64> {
+        (0..self.lo.len()).map(|i| self.component(i).mean(
+            &v.iter().map(|x| (x.0[i],x.1)).collect())).collect()
+    }
+}
+
+/// answer of a MixedDomain
+#[derive(Clone,Debug,PartialEq)]
+pub enum AnsValue {
+    Int(i32),
+    Real(f64),
+    Cat(usize),
+    Vector(Vec<f64>)
+}
+
+impl Default for AnsValue {
+    fn default() -> AnsValue {
+        AnsValue::Int(0)
+    }
+}
+
+/// domain of any of the other domains; lets a question set mix
+/// integer, real, categorical and vector questions.
+#[derive(Clone,Debug,PartialEq)]
+pub enum MixedDomain {
+    Int((i32,i32)),
+    Real(RealRange),
+    Cat(Categorical),
+    Vector(VecRange)
+}
+
+/// inner answer of variant `$v`; any other variant is a panic.
+macro_rules! mixed_unwrap {
+    ($a:expr,$v:ident) => {
+        match $a {
+            AnsValue::$v(x) => x,
+            _ => panic!("answer {:?} not of variant {}",$a,stringify!($v))
+        }
+    };
+}
+
+impl AnsDomain for MixedDomain {
+    type Ans = AnsValue;
+
+    fn contains(&self,a:&AnsValue) -> bool {
+        match (self,a) {
+            (MixedDomain::Int(d),AnsValue::Int(x)) => d.contains(x),
+            (MixedDomain::Real(d),AnsValue::Real(x)) => d.contains(x),
+            (MixedDomain::Cat(d),AnsValue::Cat(x)) => d.contains(x),
+            (MixedDomain::Vector(d),AnsValue::Vector(x)) => d.contains(x),
+            _ => false
+        }
+    }
+
+    /// answers of the wrong variant, such as the default of an unanswered
+    /// QData entry, are the domain diameter away from any answer.
+    fn distance(&self,a:&AnsValue,b:&AnsValue) -> f32 {
+        match (self,a,b) {
+            (MixedDomain::Int(d),AnsValue::Int(x),AnsValue::Int(y)) => d.distance(x,y),
+            (MixedDomain::Real(d),AnsValue::Real(x),AnsValue::Real(y)) => d.distance(x,y),
+            (MixedDomain::Cat(d),AnsValue::Cat(x),AnsValue::Cat(y)) => d.distance(x,y),
+            (MixedDomain::Vector(d),AnsValue::Vector(x),AnsValue::Vector(y)) => d.distance(x,y),
+            _ => self.diameter()
+        }
+    }
+
+    fn max_distance_from(&self,a:&AnsValue) -> f32 {
+        if !self.contains(a) {
+            return self.diameter();
+        }
+        match self {
+            MixedDomain::Int(d) => d.max_distance_from(mixed_unwrap!(a,Int)),
+            MixedDomain::Real(d) => d.max_distance_from(mixed_unwrap!(a,Real)),
+            MixedDomain::Cat(d) => d.max_distance_from(mixed_unwrap!(a,Cat)),
+            MixedDomain::Vector(d) => d.max_distance_from(mixed_unwrap!(a,Vector))
+        }
+    }
+
+    fn diameter(&self) -> f32 {
+        match self {
+            MixedDomain::Int(d) => d.diameter(),
+            MixedDomain::Real(d) => d.diameter(),
+            MixedDomain::Cat(d) => d.diameter(),
+            MixedDomain::Vector(d) => d.diameter()
+        }
+    }
+
+    fn midpoint(&self) -> AnsValue {
+        match self {
+            MixedDomain::Int(d) => AnsValue::Int(d.midpoint()),
+            MixedDomain::Real(d) => AnsValue::Real(d.midpoint()),
+            MixedDomain::Cat(d) => AnsValue::Cat(d.midpoint()),
+            MixedDomain::Vector(d) => AnsValue::Vector(d.midpoint())
+        }
+    }
+
+    fn perturb(&self,a:&AnsValue,dec_degree:f32) -> AnsValue {
+        match self {
+            MixedDomain::Int(d) => AnsValue::Int(d.perturb(mixed_unwrap!(a,Int),dec_degree)),
+            MixedDomain::Real(d) => AnsValue::Real(d.perturb(mixed_unwrap!(a,Real),dec_degree)),
+            MixedDomain::Cat(d) => AnsValue::Cat(d.perturb(mixed_unwrap!(a,Cat),dec_degree)),
+            MixedDomain::Vector(d) => AnsValue::Vector(d.perturb(mixed_unwrap!(a,Vector),dec_degree))
+        }
+    }
+
+    fn nearest(&self,a:&AnsValue) -> AnsValue {
+        if !self.contains(a) && std::mem::discriminant(a) != std::mem::discriminant(&self.midpoint()) {
+            return self.midpoint();
+        }
+        match self {
+            MixedDomain::Int(d) => AnsValue::Int(d.nearest(mixed_unwrap!(a,Int))),
+            MixedDomain::Real(d) => AnsValue::Real(d.nearest(mixed_unwrap!(a,Real))),
+            MixedDomain::Cat(d) => AnsValue::Cat(d.nearest(mixed_unwrap!(a,Cat))),
+            MixedDomain::Vector(d) => AnsValue::Vector(d.nearest(mixed_unwrap!(a,Vector)))
+        }
+    }
+
+    fn random(&self) -> AnsValue {
+        match self {
+            MixedDomain::Int(d) => AnsValue::Int(d.random()),
+            MixedDomain::Real(d) => AnsValue::Real(d.random()),
+            MixedDomain::Cat(d) => AnsValue::Cat(d.random()),
+            MixedDomain::Vector(d) => AnsValue::Vector(d.random())
+        }
+    }
+
+    /// answers of the wrong variant, such as the default of an
+    /// unanswered QData entry, are left out of the mean.
+    fn mean(&self,v:&Vec<(AnsValue,f32)>) -> AnsValue {
+        let m = self.midpoint();
+        let v2:Vec<(AnsValue,f32)> = v.iter().filter(|x| std::mem::discriminant(&x.0) ==
+            std::mem::discriminant(&m)).cloned().collect();
+        match self {
+            MixedDomain::Int(d) => AnsValue::Int(d.mean(&v2.into_iter().map(|x| (*mixed_unwrap!(&x.0,Int),x.1)).collect())),
+            MixedDomain::Real(d) => AnsValue::Real(d.mean(&v2.into_iter().map(|x| (*mixed_unwrap!(&x.0,Real),x.1)).collect())),
+            MixedDomain::Cat(d) => AnsValue::Cat(d.mean(&v2.into_iter().map(|x| (*mixed_unwrap!(&x.0,Cat),x.1)).collect())),
+            MixedDomain::Vector(d) => AnsValue::Vector(d.mean(&v2.into_iter().map(|x| (mixed_unwrap!(&x.0,Vector).clone(),x.1)).collect()))
+        }
+    }
+
+    fn as_i32(&self,a:&AnsValue) -> Option<i32> {
+        match a {
+            AnsValue::Int(x) => Some(*x),
+            _ => None
+        }
+    }
+
+    fn from_i32(&self,i:i32) -> Option<AnsValue> {
+        match self {
+            MixedDomain::Int(d) => d.from_i32(i).map(|x| AnsValue::Int(x)),
+            _ => None
+        }
+    }
+
+    fn update_mean(&self,m:&AnsValue,n:usize,a:&AnsValue) -> AnsValue {
+        if n == 0 || std::mem::discriminant(m) != std::mem::discriminant(a) {
+            return a.clone();
+        }
+        match (self,m,a) {
+            (MixedDomain::Int(d),AnsValue::Int(x),AnsValue::Int(y)) => AnsValue::Int(d.update_mean(x,n,y)),
+            _ => self.mean(&vec![(m.clone(),n as f32),(a.clone(),1.)])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__AnsDomain_int() {
+        let d = (-80,80);
+        assert_eq!(d.midpoint(),0);
+        assert_eq!(d.contradiction(&0,&40),0.5);
+        assert_eq!(d.perturb(&20,0.),20);
+        assert_eq!(d.perturb(&20,1.),-80);
+        assert_eq!(d.mean(&vec![(10,1.),(40,2.)]),30);
+        assert_eq!(d.update_mean(&0,0,&40),40);
+
+        // rounding of the answer of no node and of the default answer
+        assert_eq!((-3,2).mean(&Vec::new()),-1);
+        assert_eq!((-3,2).midpoint(),0);
+        assert_eq!(d.normalize(40.),0.25);
+    }
+
+    #[test]
+    fn test__AnsDomain_real() {
+        let d = RealRange(0.,1.);
+        assert_eq!(d.midpoint(),0.5);
+        assert_eq!(d.contradiction(&0.25,&1.),1.);
+        assert_eq!(d.perturb(&0.25,1.),1.);
+        for _ in 0..20 {
+            let x = d.perturb(&0.5,0.5);
+            assert!(x == 0.25 || x == 0.75);
+        }
+        assert!((d.mean(&vec![(0.2,1.),(0.8,3.)]) - 0.65).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test__AnsDomain_categorical() {
+        let d = build_Categorical(vec!["red","green","blue"]);
+        assert_eq!(d.midpoint(),1);
+        assert_eq!(d.contradiction(&0,&2),1.);
+        assert_eq!(d.contradiction(&2,&2),0.);
+        for _ in 0..20 {
+            assert!(d.perturb(&1,1.) != 1);
+            assert_eq!(d.perturb(&1,0.),1);
+        }
+        assert_eq!(d.mean(&vec![(0,1.),(2,1.),(2,0.5)]),2);
+        assert_eq!(d.mean(&vec![(0,1.),(7,5.)]),0);
+        assert_eq!(d.mean(&vec![(7,5.)]),1);
+    }
+
+    #[test]
+    fn test__AnsDomain_vector() {
+        let d = build_VecRange(vec![0.,0.],vec![3.,4.]);
+        assert_eq!(d.diameter(),5.);
+        assert_eq!(d.max_distance_from(&vec![0.,0.]),5.);
+        assert_eq!(d.perturb(&vec![0.,0.],1.),vec![3.,4.]);
+        assert_eq!(d.contradiction(&vec![0.,0.],&vec![3.,4.]),1.);
+        assert!(!d.contains(&vec![1.]));
+    }
+
+    #[test]
+    fn test__AnsDomain_mixed() {
+        let d = MixedDomain::Cat(build_Categorical(vec!["yes","no"]));
+        assert_eq!(d.midpoint(),AnsValue::Cat(1));
+        assert_eq!(d.nearest(&AnsValue::Int(0)),AnsValue::Cat(1));
+        assert_eq!(d.mean(&vec![(AnsValue::Int(0),4.),(AnsValue::Cat(0),1.)]),AnsValue::Cat(0));
+
+        let d2 = MixedDomain::Int((0,10));
+        assert_eq!(d2.contradiction(&AnsValue::Int(10),&AnsValue::Int(5)),0.5);
+        assert_eq!(d2.from_i32(11),None);
+        assert_eq!(d2.distance(&AnsValue::Int(3),&AnsValue::Cat(0)),10.);
+    }
+}
@@ -0,0 +1,262 @@
+//! node deception classifier.
+//! Q labels each (node,question) pair it has answers for as honest,
+//! deceptive or erratic from its QData; labels are evaluated against
+//! the node objectives RNDB::obj of the scenario.
+use crate::q_struct;
+use crate::rnetwork;
+use crate::ans_domain::AnsDomain;
+use crate::id_registry::NodeId;
+use ndarray::Dim;
+use std::collections::HashMap;
+use std::fmt;
+
+/// label of a node for a question; index matches objective of RNDB::obj
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum NodeLabel {
+    Honest = 0,
+    Deceptive = 1,
+    Erratic = 2
+}
+
+pub fn obj_to_label(o:usize) -> NodeLabel {
+    match o {
+        0 => NodeLabel::Honest,
+        1 => NodeLabel::Deceptive,
+        _ => NodeLabel::Erratic
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct Classification {
+    pub label: NodeLabel,
+    // mean contradiction of node; for ZTest, the z statistic
+    pub score: f32
+}
+
+/// classifiers of (node,question) pairs
+#[derive(Clone,Debug)]
+pub enum DeceptionClassifier {
+    /// honest if mean contradiction < `honest`; else erratic if the standard
+    /// deviation of contradiction > `erratic`; else deceptive.
+    Threshold{honest:f32,erratic:f32},
+    /// honest if the z statistic of mean contradiction against `mu0` is at
+    /// most `z`; else erratic if the standard deviation of contradiction
+    /// > `erratic`; else deceptive.
+    ZTest{mu0:f32,z:f32,erratic:f32}
+}
+
+/// # description
+/// (mean,standard deviation,count) of contradiction of node `ni` for `qi`.
+/// Uses the direct answers of node in QData.al against Q's answer; if there
+/// are none, QData.x with standard deviation 0. None if node has no data.
+pub fn pair_stats<D:AnsDomain>(q:&mut q_struct::QStruct<D>,ni:usize,qi:usize) -> Option<(f32,f32,usize)> {
+    let d = Dim((ni,qi));
+    if !q.rd.al.contains_key(&(ni,qi)) {
+        if q.rd.y[d] == 0 && q.rd.w[d] == 0 {
+            return None;
+        }
+        return Some((q.rd.x[d],0.,q.rd.y[d].max(1)));
+    }
+
+    let a = q.ans_to_q(qi);
+    let dom = q.qs[qi].ans_range.clone();
+    let c:Vec<f32> = q.rd.al[&(ni,qi)].iter().map(|x| dom.contradiction(&a,x)).collect();
+    let n = c.len();
+    let m = c.iter().sum::<f32>() / n as f32;
+    let sd = if n < 2 {0.} else {(c.iter().map(|x| (x - m) * (x - m)).sum::<f32>() / (n - 1) as f32).sqrt()};
+    Some((m,sd,n))
+}
+
+/// # description
+/// classifies node `ni` for question `qi`; None if Q has no data on the pair.
+pub fn classify_pair<D:AnsDomain>(cl:&DeceptionClassifier,q:&mut q_struct::QStruct<D>,ni:usize,qi:usize) -> Option<Classification> {
+    let x = pair_stats(q,ni,qi);
+    if x.is_none() {
+        return None;
+    }
+    let (m,sd,n) = x.unwrap();
+
+    let (honest,score,erratic) = match cl {
+        DeceptionClassifier::Threshold{honest,erratic} => (m < *honest,m,*erratic),
+        DeceptionClassifier::ZTest{mu0,z,erratic} => {
+            // standard error of at least 1 / n for a zero deviation
+            let se = (sd / (n as f32).sqrt()).max(1. / n as f32);
+            let zs = (m - mu0) / se;
+            (zs <= *z,zs,*erratic)
+        }
+    };
+
+    let l = if honest {NodeLabel::Honest} else if sd > erratic {NodeLabel::Erratic} else {NodeLabel::Deceptive};
+    Some(Classification{label:l,score:score})
+}
+
+/// classification of every (node,question) pair with data
+pub fn classify_all<D:AnsDomain>(cl:&DeceptionClassifier,q:&mut q_struct::QStruct<D>) -> HashMap<(usize,usize),Classification> {
+    let (r,c) = q.rd.y.dim();
+    let mut h: HashMap<(usize,usize),Classification> = HashMap::new();
+    for i in 0..r {
+        for j in 0..c {
+            let x = classify_pair(cl,q,i,j);
+            if !x.is_none() {
+                h.insert((i,j),x.unwrap());
+            }
+        }
+    }
+    h
+}
+
+/// # description
+/// label of each node: the most frequent label over its questions, ties
+/// to the less honest label; score is the mean score of those questions.
+pub fn node_labels(h:&HashMap<(usize,usize),Classification>) -> HashMap<usize,Classification> {
+    let mut m: HashMap<usize,Vec<Classification>> = HashMap::new();
+    for (k,v) in h.iter() {
+        m.entry(k.0).or_insert(Vec::new()).push(v.clone());
+    }
+
+    m.into_iter().map(|(k,v)| {
+        let mut c = [0;3];
+        for x in v.iter() {
+            c[x.label as usize] += 1;
+        }
+        let l = (0..3).fold(0,|x,x2| if c[x2] >= c[x] {x2} else {x});
+        let s = v.iter().map(|x| x.score).sum::<f32>() / v.len() as f32;
+        (k,Classification{label:obj_to_label(l),score:s})
+    }).collect()
+}
+
+/// evaluation of labels of (node,question) pairs against RNDB::obj
+#[derive(Clone,Debug)]
+pub struct ClassifierEval {
+    pub round: usize,
+    // [actual][predicted]
+    pub confusion: [[usize;3];3],
+    // (external id of node,question) of pairs labeled other than actual
+    pub wrong: Vec<(NodeId,usize)>
+}
+
+impl ClassifierEval {
+
+    /// precision of label l; None if l is never predicted
+    pub fn precision(&self,l:NodeLabel) -> Option<f32> {
+        let i = l as usize;
+        let s:usize = (0..3).map(|a| self.confusion[a][i]).sum();
+        if s == 0 {
+            return None;
+        }
+        Some(self.confusion[i][i] as f32 / s as f32)
+    }
+
+    /// recall of label l; None if l is never actual
+    pub fn recall(&self,l:NodeLabel) -> Option<f32> {
+        let i = l as usize;
+        let s:usize = self.confusion[i].iter().sum();
+        if s == 0 {
+            return None;
+        }
+        Some(self.confusion[i][i] as f32 / s as f32)
+    }
+
+    pub fn accuracy(&self) -> Option<f32> {
+        let s:usize = self.confusion.iter().map(|r| r.iter().sum::<usize>()).sum();
+        if s == 0 {
+            return None;
+        }
+        Some((0..3).map(|i| self.confusion[i][i]).sum::<usize>() as f32 / s as f32)
+    }
+}
+
+impl fmt::Display for ClassifierEval {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut q = format!("round {} confusion [actual][predicted]\n{:?}\n",self.round,self.confusion);
+        for l in vec![NodeLabel::Honest,NodeLabel::Deceptive,NodeLabel::Erratic].into_iter() {
+            q.push_str(&format!("{:?} precision {:?} recall {:?}\n",l,self.precision(l),self.recall(l)));
+        }
+        let w:Vec<String> = self.wrong.iter().map(|(n,qi)| format!("({},{})",n,qi)).collect();
+        q.push_str(&format!("mislabeled (node,question): {}\n",w.join(" ")));
+        write!(f, "{}", q)
+    }
+}
+
+/// # description
+/// compares labels of (node,question) pairs with the objectives of the
+/// nodes in network `rn`.
+pub fn evaluate<A>(round:usize,h:&HashMap<(usize,usize),Classification>,rn:&rnetwork::RNetwork<A>) -> ClassifierEval {
+    let mut c = [[0;3];3];
+    let mut w: Vec<(NodeId,usize)> = Vec::new();
+    for n in rn.nodes.iter() {
+        for (qi,o) in n.db.obj.iter() {
+            if !h.contains_key(&(n.idn,*qi)) {
+                continue;
+            }
+            let (a,p) = (obj_to_label(*o),h[&(n.idn,*qi)].label);
+            c[a as usize][p as usize] += 1;
+            if a != p {
+                w.push((rn.idn_to_external(n.idn).clone(),*qi));
+            }
+        }
+    }
+    w.sort();
+    ClassifierEval{round:round,confusion:c,wrong:w}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn log_direct(q:&mut q_struct::QStruct,ni:usize,qi:usize,v:Vec<i32>) {
+        for x in v.into_iter() {
+            q.response_to_nodeset(ni,HashSet::from_iter(vec![ni]),qi,x);
+        }
+    }
+
+    #[test]
+    fn test__classify_pair() {
+        // question 0 known answer 50 in (0,100)
+        let mut q = q_struct::sample_QStruct1();
+        log_direct(&mut q,0,0,vec![50,50,50,50]);
+        log_direct(&mut q,2,0,vec![100,100,100,100]);
+        log_direct(&mut q,6,0,vec![0,90,20,100]);
+
+        let cl = DeceptionClassifier::Threshold{honest:0.2,erratic:0.1};
+        assert_eq!(classify_pair(&cl,&mut q,0,0).unwrap().label,NodeLabel::Honest);
+        assert_eq!(classify_pair(&cl,&mut q,2,0).unwrap(),Classification{label:NodeLabel::Deceptive,score:1.});
+        assert_eq!(classify_pair(&cl,&mut q,6,0).unwrap().label,NodeLabel::Erratic);
+        assert!(classify_pair(&cl,&mut q,1,0).is_none());
+
+        let cl2 = DeceptionClassifier::ZTest{mu0:0.1,z:1.96,erratic:0.1};
+        assert_eq!(classify_pair(&cl2,&mut q,0,0).unwrap().label,NodeLabel::Honest);
+        assert_eq!(classify_pair(&cl2,&mut q,2,0).unwrap().label,NodeLabel::Deceptive);
+        assert_eq!(classify_pair(&cl2,&mut q,6,0).unwrap().label,NodeLabel::Erratic);
+
+        let h = classify_all(&cl,&mut q);
+        assert_eq!(h.len(),3);
+        let nl = node_labels(&h);
+        assert_eq!(nl[&6].label,NodeLabel::Erratic);
+    }
+
+    #[test]
+    fn test__evaluate() {
+        let mut q = q_struct::sample_QStruct1();
+        let rn = rnetwork::sample_RNBNetwork1();
+
+        // node 0 honest, node 2 deceptive, node 6 contradiction,
+        // node 5 honest with wrong known answer.
+        log_direct(&mut q,0,0,vec![50,50]);
+        log_direct(&mut q,2,0,vec![100,100]);
+        log_direct(&mut q,6,0,vec![0,90,20]);
+        log_direct(&mut q,5,0,vec![25,25]);
+
+        let cl = DeceptionClassifier::Threshold{honest:0.2,erratic:0.1};
+        let h = classify_all(&cl,&mut q);
+        let e = evaluate(3,&h,&rn);
+        assert_eq!(e.confusion,[[1,1,0],[0,1,0],[0,0,1]]);
+        assert_eq!(e.wrong,vec![(NodeId::Num(5),0)]);
+        assert_eq!(e.precision(NodeLabel::Deceptive),Some(0.5));
+        assert_eq!(e.recall(NodeLabel::Honest),Some(0.5));
+        assert_eq!(e.recall(NodeLabel::Erratic),Some(1.));
+        assert_eq!(e.accuracy(),Some(0.75));
+    }
+}
@@ -0,0 +1,170 @@
+//! collusion groups of nodes.
+//! Members of a group answer a shared fake answer to each question, vouch
+//! for each other as delegates, and optionally take turns absorbing the
+//! resistance loss of a direct answer.
+use crate::rnb_env;
+use crate::fuel;
+use crate::std_rng;
+use crate::ans_domain::AnsDomain;
+use crate::id_registry::NodeId;
+use std::collections::{HashMap,HashSet};
+use std::fmt;
+
+#[derive(Clone,Debug)]
+pub struct CollusionGroup<A = i32> {
+    pub members: Vec<NodeId>,
+    // question -> shared answer of members
+    pub fake_ans: HashMap<usize,A>,
+    // satisfaction rate that members hold of each other
+    pub vouch: f32,
+    // members take turns absorbing resistance loss
+    pub rotate: bool,
+    // index of the member absorbing the next loss
+    pub turn: usize
+}
+
+pub fn build_CollusionGroup<A>(members:Vec<NodeId>,fake_ans:HashMap<usize,A>,vouch:f32,rotate:bool) -> CollusionGroup<A> {
+    assert!(members.len() > 0);
+    CollusionGroup{members:members,fake_ans:fake_ans,vouch:vouch,rotate:rotate,turn:0}
+}
+
+impl<A> CollusionGroup<A> {
+
+    pub fn contains(&self,id:&NodeId) -> bool {
+        self.members.contains(id)
+    }
+
+    /// # description
+    /// member that absorbs the next resistance loss of the group among
+    /// the members in `alive`; advances the turn.
+    pub fn next_absorber(&mut self,alive:&HashSet<NodeId>) -> Option<NodeId> {
+        let l = self.members.len();
+        for i in 0..l {
+            let j = (self.turn + i) % l;
+            if alive.contains(&self.members[j]) {
+                self.turn = (j + 1) % l;
+                return Some(self.members[j].clone());
+            }
+        }
+        None
+    }
+}
+
+/// index of the group of node `id`
+pub fn group_of<A>(groups:&Vec<CollusionGroup<A>>,id:&NodeId) -> Option<usize> {
+    groups.iter().position(|g| g.contains(id))
+}
+
+/// fuel Q spends against nodes that collude and against the same
+/// nodes acting alone
+#[derive(Clone,Debug,Default)]
+pub struct CollusionCost {
+    pub rounds_with: usize,
+    pub rounds_alone: usize,
+    pub fuel_with: i32,
+    pub fuel_alone: i32,
+    // fuel of transactions that target members
+    pub member_fuel_with: i32,
+    pub member_fuel_alone: i32,
+    // members whose resistance ran out
+    pub dead_with: usize,
+    pub dead_alone: usize
+}
+
+impl CollusionCost {
+
+    pub fn extra_fuel(&self) -> i32 {
+        self.fuel_with - self.fuel_alone
+    }
+
+    /// fuel with collusion over fuel alone; None if Q spent no fuel alone
+    pub fn fuel_ratio(&self) -> Option<f32> {
+        if self.fuel_alone == 0 {
+            return None;
+        }
+        Some(self.fuel_with as f32 / self.fuel_alone as f32)
+    }
+}
+
+impl fmt::Display for CollusionCost {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "collusion: rounds {} fuel {} member fuel {} dead members {}\nalone: rounds {} fuel {} member fuel {} dead members {}\nextra fuel {} ratio {:?}\n",
+            self.rounds_with,self.fuel_with,self.member_fuel_with,self.dead_with,
+            self.rounds_alone,self.fuel_alone,self.member_fuel_alone,self.dead_alone,
+            self.extra_fuel(),self.fuel_ratio())
+    }
+}
+
+/// # description
+/// runs environment `r` for at most `rounds` rounds.
+/// # return
+/// (rounds run, fuel spent, fuel spent on `members`, dead `members`)
+pub fn run_fuel<D:AnsDomain>(r:&mut rnb_env::RNBENV<D>,rounds:usize,members:&HashSet<NodeId>) -> (usize,i32,i32,usize) {
+    let mut c = 0;
+    while c < rounds {
+        r.new_round(c);
+        let stat = r.execute_round(false);
+        c += 1;
+        if !(r.summarize_stats(false) && stat) {
+            break;
+        }
+    }
+
+    let q = r.fetch_QStruct();
+    let ids = q.rd.ids.clone().unwrap();
+    let mut s = 0;
+    let mut sm = 0;
+    for x in q.fuel.log.iter() {
+        if x.action == fuel::FuelAction::Regen {
+            continue;
+        }
+        s -= x.delta;
+        if !x.node.is_none() && members.contains(x.node.as_ref().unwrap()) {
+            sm -= x.delta;
+        }
+    }
+    let d = q.dead_nodes.iter().filter(|x| members.contains(ids.id(**x))).count();
+    (c,s,sm,d)
+}
+
+/// # description
+/// compares the fuel Q spends on scenario `build` with its collusion
+/// groups against the same scenario with the groups removed; both runs
+/// start from random seed `seed`.
+pub fn compare_collusion<D:AnsDomain>(build:&dyn Fn() -> rnb_env::RNBENV<D>,rounds:usize,seed:u64) -> CollusionCost {
+    std_rng::set_seed(seed);
+    let mut r = build();
+    let members:HashSet<NodeId> = r.collusion.iter().map(|g| g.members.clone()).flatten().collect();
+    let (c,s,sm,d) = run_fuel(&mut r,rounds,&members);
+
+    std_rng::set_seed(seed);
+    let mut r2 = build();
+    r2.collusion = Vec::new();
+    let (c2,s2,sm2,d2) = run_fuel(&mut r2,rounds,&members);
+
+    CollusionCost{rounds_with:c,rounds_alone:c2,fuel_with:s,fuel_alone:s2,
+        member_fuel_with:sm,member_fuel_alone:sm2,dead_with:d,dead_alone:d2}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__CollusionGroup_next_absorber() {
+        let mut g = build_CollusionGroup::<i32>(vec![NodeId::Num(2),NodeId::Num(3),NodeId::Num(8)],
+            HashMap::new(),1.,true);
+        let mut alive:HashSet<NodeId> = g.members.iter().cloned().collect();
+        assert_eq!(g.next_absorber(&alive),Some(NodeId::Num(2)));
+        assert_eq!(g.next_absorber(&alive),Some(NodeId::Num(3)));
+        alive.remove(&NodeId::Num(8));
+        assert_eq!(g.next_absorber(&alive),Some(NodeId::Num(2)));
+        alive.clear();
+        assert_eq!(g.next_absorber(&alive),None);
+
+        let v = vec![g.clone()];
+        assert_eq!(group_of(&v,&NodeId::Num(3)),Some(0));
+        assert_eq!(group_of(&v,&NodeId::Num(4)),None);
+    }
+}
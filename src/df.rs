@@ -9,12 +9,12 @@ delegation path: tree structure represented as a hashmap.
                  travel starts at key head. 
 */
 #[derive(Clone)]
-pub struct DPath {
+pub struct DPath<A = i32> {
     // search map: node to qualifying neighbors
     pub sm: HashMap<usize,Vec<usize>>,
 
     // node answers
-    pub na: HashMap<usize,i32>,
+    pub na: HashMap<usize,A>,
 
     // map head
     pub head:usize,
@@ -23,7 +23,7 @@ pub struct DPath {
 
 }
 
-impl fmt::Display for DPath {
+impl<A:fmt::Debug> fmt::Display for DPath<A> {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s1 = format!("search map\n{:?}\n",self.sm);
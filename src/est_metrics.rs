@@ -0,0 +1,107 @@
+//! metrics of how well Q estimates answers to questions it does not
+//! know, against a ground truth that is hidden from Q.
+use crate::ans_domain::AnsDomain;
+use std::collections::HashMap;
+use std::fmt;
+
+/// estimate of Q for one question in one round
+#[derive(Clone,Debug)]
+pub struct EstimationRecord<A> {
+    pub round: usize,
+    pub est: A,
+    // distance of estimate to ground truth
+    pub abs_err: f32,
+    // abs_err scaled by the answer domain diameter
+    pub norm_err: f32
+}
+
+/// history of estimates of each question with ground truth
+#[derive(Clone)]
+pub struct EstimationMetrics<A> {
+    // normalized error at most tolerance is a stable estimate
+    pub tolerance: f32,
+    // question -> records by round
+    pub history: HashMap<usize,Vec<EstimationRecord<A>>>
+}
+
+pub fn build_EstimationMetrics<A>(tolerance:f32) -> EstimationMetrics<A> {
+    EstimationMetrics{tolerance:tolerance,history:HashMap::new()}
+}
+
+impl<A:Clone> EstimationMetrics<A> {
+
+    /// # description
+    /// records estimate `est` of question `qi` against ground truth `gt`
+    /// in domain `dom`.
+    pub fn record<D:AnsDomain<Ans=A>>(&mut self,round:usize,qi:usize,dom:&D,est:A,gt:&A) {
+        let d = dom.distance(&est,gt);
+        let r = EstimationRecord{round:round,est:est,abs_err:d,norm_err:dom.normalize(d)};
+        self.history.entry(qi).or_insert(Vec::new()).push(r);
+    }
+
+    /// # description
+    /// first round from which every recorded estimate of `qi` is within
+    /// tolerance; None if the last estimate is not.
+    pub fn stabilized_round(&self,qi:usize) -> Option<usize> {
+        if !self.history.contains_key(&qi) {
+            return None;
+        }
+
+        let mut r: Option<usize> = None;
+        for x in self.history[&qi].iter() {
+            if x.norm_err > self.tolerance {
+                r = None;
+            } else if r.is_none() {
+                r = Some(x.round);
+            }
+        }
+        r
+    }
+
+    /// last record of `qi`
+    pub fn last(&self,qi:usize) -> Option<EstimationRecord<A>> {
+        if !self.history.contains_key(&qi) {
+            return None;
+        }
+        self.history[&qi].last().cloned()
+    }
+}
+
+impl<A:fmt::Debug> fmt::Display for EstimationMetrics<A> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut k:Vec<&usize> = self.history.keys().collect();
+        k.sort();
+        let mut q = "".to_string();
+        for qi in k.into_iter() {
+            let x = self.history[qi].last().unwrap();
+            q.push_str(&format!("question {} round {} estimate {:?} error {} ({:.3})\n",
+                qi,x.round,x.est,x.abs_err,x.norm_err));
+        }
+        write!(f, "{}", q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__EstimationMetrics_stabilized_round() {
+        let mut m = build_EstimationMetrics(0.05);
+        let d = (-80,80);
+        let v = vec![0,30,38,50,41,40,39];
+        for (i,x) in v.into_iter().enumerate() {
+            m.record(i,1,&d,x,&40);
+        }
+
+        // rounds 2, 4.. are within 8 of 40
+        assert_eq!(m.stabilized_round(1),Some(4));
+        assert_eq!(m.last(1).unwrap().abs_err,1.);
+        assert_eq!(m.history[&1][0].norm_err,0.25);
+
+        m.record(7,1,&d,-5,&40);
+        assert_eq!(m.stabilized_round(1),None);
+        assert_eq!(m.stabilized_round(0),None);
+    }
+}
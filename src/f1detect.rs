@@ -0,0 +1,138 @@
+//! detection of F1 answer patterns.
+//! Q uses the functions in this file on the sequence of answers
+//! a node gives to a question to infer if the node's answers are
+//! periodic or deterministic (as is the case for an F1-fixed node).
+use crate::seq_encoder;
+use crate::ans_domain::AnsDomain;
+use ndarray::Array1;
+
+/// output of a successful detection
+#[derive(Clone,Debug,PartialEq)]
+pub struct F1Detection<A = i32> {
+    // period of answers; None if detected by IndexFractionNotation
+    pub period: Option<usize>,
+    // predicted next answer
+    pub next: A
+}
+
+/// # description
+/// calculates the smallest period p such that the last `reps` * p
+/// values of `v` repeat with period p.
+pub fn suffix_period<A:PartialEq>(v:&Vec<A>,reps:usize) -> Option<usize> {
+    assert!(reps >= 2);
+    let n = v.len();
+    for p in 1..(n / reps + 1) {
+        let mut stat = true;
+        for k in 0..(reps - 1) * p {
+            if v[n - 1 - k] != v[n - 1 - k - p] {
+                stat = false;
+                break;
+            }
+        }
+
+        if stat {
+            return Some(p);
+        }
+    }
+    None
+}
+
+/// # description
+/// encodes the first n - h values of `v` by IndexFractionNotation and
+/// outputs the next value if the encoding outputs the last h values of `v`.
+pub fn ifn_prediction(v:&Vec<i32>,h:usize) -> Option<i32> {
+    let n = v.len();
+    if h == 0 || n <= h + 1 {
+        return None;
+    }
+
+    let mut ifn = seq_encoder::build_index_fraction_notation(Array1::from_vec(v[..n - h].to_vec()));
+    ifn.process();
+    for i in n - h..n {
+        if ifn.output(i as i32) != v[i] {
+            return None;
+        }
+    }
+
+    // encode all values for the prediction
+    let mut ifn2 = seq_encoder::build_index_fraction_notation(Array1::from_vec(v.clone()));
+    ifn2.process();
+    Some(ifn2.output(n as i32))
+}
+
+/// # description
+/// detects if the answer sequence `v` in domain `dom` is periodic or
+/// deterministic. Uses the following chain:
+/// (1) find the smallest period that repeats `reps` times at the end of `v`.
+/// (2) if there is none and `dom` has an integer view, check that 
+///     IndexFractionNotation on the first values of `v` outputs the last
+///     `reps` values.
+pub fn detect_pattern<D:AnsDomain>(v:&Vec<D::Ans>,reps:usize,dom:&D) -> Option<F1Detection<D::Ans>> {
+    let n = v.len();
+    if n < reps {
+        return None;
+    }
+
+    let p = suffix_period(v,reps);
+    if !p.is_none() {
+        let p2 = p.unwrap();
+        return Some(F1Detection{period:Some(p2),next:v[n - p2].clone()});
+    }
+
+    let v2:Vec<Option<i32>> = v.iter().map(|x| dom.as_i32(x)).collect();
+    if v2.iter().any(|x| x.is_none()) {
+        return None;
+    }
+
+    let x = ifn_prediction(&v2.into_iter().map(|x| x.unwrap()).collect(),reps);
+    if x.is_none() {
+        return None;
+    }
+
+    // case: prediction is not in domain
+    let x2 = dom.from_i32(x.unwrap());
+    if x2.is_none() {
+        return None;
+    }
+    Some(F1Detection{period:None,next:x2.unwrap()})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__suffix_period() {
+        let v = vec![4,-3,1,2,7,1,2,7,1,2,7];
+        assert_eq!(suffix_period(&v,3),Some(3));
+        assert_eq!(suffix_period(&v,4),None);
+
+        let v2 = vec![8,9,5,5,5];
+        assert_eq!(suffix_period(&v2,3),Some(1));
+
+        let v3 = vec![1,2,3,4,5,6];
+        assert_eq!(suffix_period(&v3,2),None);
+    }
+
+    #[test]
+    fn test__detect_pattern() {
+        // case: periodic
+        let d = (-100,100);
+        let v = vec![40,11,3,6,3,6,3,6];
+        assert_eq!(detect_pattern(&v,3,&d),Some(F1Detection{period:Some(2),next:3}));
+
+        // case: deterministic, not periodic
+        let v2 = vec![0,5,10,15,20,25,30];
+        assert_eq!(detect_pattern(&v2,3,&d),Some(F1Detection{period:None,next:35}));
+
+        // case: prediction out of domain
+        assert_eq!(detect_pattern(&v2,3,&(0,30)),None);
+
+        // case: no pattern
+        let v3 = vec![3,-10,22,4,17,-8,1];
+        assert_eq!(detect_pattern(&v3,3,&d),None);
+
+        // case: too few answers
+        assert_eq!(detect_pattern(&vec![5,5],3,&d),None);
+    }
+}
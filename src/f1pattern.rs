@@ -9,14 +9,14 @@ use ndarray::{Array2,Dim};
 /// data is r x c 2-d matrix
 /// row is question
 /// column is answer
-pub struct F1P {
+pub struct F1P<A = i32> {
     irfvec: Vec<cng::IRFDNG>,
-    data: Array2<i32>
+    data: Array2<A>
 }
 
 /// data is 2-d matrix, each row i corresponds to m responses
 /// from node on question i  
-pub fn build_std_random_F1P(data:Array2<i32>) -> F1P {
+pub fn build_std_random_F1P<A>(data:Array2<A>) -> F1P<A> {
     let (r,c) = data.dim();
     let mut irfvec: Vec<cng::IRFDNG> = Vec::new(); 
     for i in 0..r {
@@ -27,11 +27,21 @@ pub fn build_std_random_F1P(data:Array2<i32>) -> F1P {
     F1P{irfvec:irfvec,data:data}
 }
 
-impl F1P {
+impl<A:Clone> F1P<A> {
+
+    pub fn next(&mut self,qi:usize) -> A {
+        // case: pattern of width 1
+        if self.data.dim().1 == 1 {
+            return self.data[Dim((qi,0))].clone();
+        }
 
-    pub fn next(&mut self,qi:usize) -> i32 {
         let ni = self.irfvec[qi].next() as usize; 
         self.data[Dim((qi,ni))].clone()
     }
 
+    /// answers of pattern; row i is for question i
+    pub fn data(&self) -> &Array2<A> {
+        &self.data
+    }
+
 }
\ No newline at end of file
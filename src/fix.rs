@@ -0,0 +1,446 @@
+//! interventions of Q on the network.
+//! A Fix checks if it applies, has a fuel cost, changes the network or Q
+//! when applied and may be reverted, by hand or when it expires. RNBENV
+//! keeps a registry of active fixes and consults it when nodes answer,
+//! when delegates answer and when Q logs a response.
+use crate::rnb_env::RNBENV;
+use crate::rnode;
+use crate::f1pattern;
+use crate::fuel;
+use crate::node_state::NodeEvent;
+use crate::ans_domain::AnsDomain;
+use std::collections::HashMap;
+
+pub trait Fix<D:AnsDomain> {
+
+    fn name(&self) -> String;
+
+    /// fuel action, node charged and score of the cost of fix
+    fn fuel_action(&self) -> fuel::FuelAction;
+    fn target(&self) -> Option<usize>;
+    fn score(&self) -> i32;
+
+    /// if fix has an effect on `env`
+    fn applicable(&self,env:&mut RNBENV<D>) -> bool;
+
+    fn apply(&mut self,env:&mut RNBENV<D>);
+
+    /// undoes `apply`
+    fn revert(&mut self,_env:&mut RNBENV<D>) {}
+
+    /// round at which fix is reverted; None for a permanent fix
+    fn expiry(&self) -> Option<usize> {
+        None
+    }
+
+    /// sets the round of expiry; used to renew a fix
+    fn set_expiry(&mut self,_round:Option<usize>) {}
+
+    /// node whose behaviour fix restricts
+    fn restricted(&self) -> Option<usize> {
+        self.target()
+    }
+
+    /// answer of node `ni` to question `qi` under fix; None for no change.
+    /// A fix may advance its state on each answer.
+    fn answer(&mut self,_ni:usize,_qi:usize) -> Option<D::Ans> {
+        None
+    }
+
+    /// node that answers by an answer pattern of fix (F1)
+    fn pattern_node(&self) -> Option<usize> {
+        None
+    }
+
+    /// if node `ni` can answer as a delegate
+    fn allows_delegate(&self,_ni:usize) -> bool {
+        true
+    }
+
+    /// changes the answers of a response of node `srcidn` before Q logs
+    /// it; the answer of `srcidn` has to remain.
+    fn filter_log(&self,_srcidn:usize,_answers:&mut HashMap<usize,D::Ans>) {}
+
+    /// moves fix to the new idns `f` of nodes after a node is removed
+    /// # return
+    /// false if fix is on a removed node and is dropped
+    fn remap_nodes(&mut self,_f:&dyn Fn(usize) -> Option<usize>) -> bool {
+        true
+    }
+
+    fn box_clone(&self) -> Box<dyn Fix<D>>;
+}
+
+/// fix in the registry of RNBENV
+pub struct ActiveFix<D:AnsDomain> {
+    pub fix: Box<dyn Fix<D>>,
+    // interrogator that applied the fix
+    pub q: usize,
+    // round of application
+    pub round: usize
+}
+
+impl<D:AnsDomain> Clone for ActiveFix<D> {
+
+    fn clone(&self) -> Self {
+        ActiveFix{fix:self.fix.box_clone(),q:self.q,round:self.round}
+    }
+}
+
+/// F1: exhausted node answers by a pattern
+#[derive(Clone)]
+pub struct F1Fix<A = i32> {
+    pub node: usize,
+    // number of answers per question of pattern
+    pub width: usize,
+    pub expires: Option<usize>,
+    // pattern made on apply
+    pub pattern: Option<f1pattern::F1P<A>>
+}
+
+pub fn build_F1Fix<A>(node:usize,width:usize,expires:Option<usize>) -> F1Fix<A> {
+    F1Fix{node:node,width:width,expires:expires,pattern:None}
+}
+
+impl<D:AnsDomain> Fix<D> for F1Fix<D::Ans> {
+
+    fn name(&self) -> String {
+        format!("F1 node {}",self.node)
+    }
+
+    fn fuel_action(&self) -> fuel::FuelAction {
+        fuel::FuelAction::F1
+    }
+
+    fn target(&self) -> Option<usize> {
+        Some(self.node)
+    }
+
+    fn score(&self) -> i32 {
+        self.width as i32
+    }
+
+    /// node has no resistance and is not fixed yet
+    fn applicable(&self,env:&mut RNBENV<D>) -> bool {
+        self.node < env.fetch_network().nodes.len() && env.node_state(self.node).transition(NodeEvent::FixF1).is_some()
+    }
+
+    fn apply(&mut self,env:&mut RNBENV<D>) {
+        // collect the ansrange vec
+        let q = env.fetch_QStruct();
+        let qrvec:Vec<D> = q.qs.iter().map(|x| x.ans_range.clone()).collect();
+        let wanted:Vec<D::Ans> = q.ans_vec().into_iter().collect();
+        let src = env.f1_src.clone();
+        let rn = env.fetch_network();
+        let f1 = rnode::F1_anspattern(&mut rn.nodes[self.node],&mut rn.ans_box,qrvec,
+            wanted,self.width,src);
+        self.pattern = Some(f1);
+    }
+
+    fn revert(&mut self,_env:&mut RNBENV<D>) {
+        self.pattern = None;
+    }
+
+    fn pattern_node(&self) -> Option<usize> {
+        Some(self.node)
+    }
+
+    /// next answer of pattern; None for a question added after the fix
+    fn answer(&mut self,ni:usize,qi:usize) -> Option<D::Ans> {
+        if ni != self.node || self.pattern.is_none() || qi >= self.pattern.as_ref().unwrap().data().dim().0 {
+            return None;
+        }
+        Some(self.pattern.as_mut().unwrap().next(qi))
+    }
+
+    fn remap_nodes(&mut self,f:&dyn Fn(usize) -> Option<usize>) -> bool {
+        f(self.node).map(|x| self.node = x).is_some()
+    }
+
+    fn expiry(&self) -> Option<usize> {
+        self.expires
+    }
+
+    fn set_expiry(&mut self,round:Option<usize>) {
+        self.expires = round;
+    }
+
+    fn box_clone(&self) -> Box<dyn Fix<D>> {
+        Box::new(self.clone())
+    }
+}
+
+/// F2: node can no longer be a delegate
+#[derive(Clone,Debug,PartialEq)]
+pub struct F2Fix {
+    pub node: usize,
+    // `qbot_function_1` score
+    pub score: i32,
+    pub expires: Option<usize>
+}
+
+impl<D:AnsDomain> Fix<D> for F2Fix {
+
+    fn name(&self) -> String {
+        format!("F2 node {}",self.node)
+    }
+
+    fn fuel_action(&self) -> fuel::FuelAction {
+        fuel::FuelAction::F2
+    }
+
+    fn target(&self) -> Option<usize> {
+        Some(self.node)
+    }
+
+    fn score(&self) -> i32 {
+        self.score
+    }
+
+    /// node is alive, not fixed by F2 yet and was a delegate
+    fn applicable(&self,env:&mut RNBENV<D>) -> bool {
+        env.fetch_QStruct().is_F2_target(self.node)
+    }
+
+    fn apply(&mut self,env:&mut RNBENV<D>) {
+        env.fetch_QStruct().f2_nodes.insert(self.node);
+    }
+
+    fn revert(&mut self,env:&mut RNBENV<D>) {
+        env.fetch_QStruct().f2_nodes.remove(&self.node);
+    }
+
+    fn allows_delegate(&self,ni:usize) -> bool {
+        ni != self.node
+    }
+
+    fn remap_nodes(&mut self,f:&dyn Fn(usize) -> Option<usize>) -> bool {
+        f(self.node).map(|x| self.node = x).is_some()
+    }
+
+    fn expiry(&self) -> Option<usize> {
+        self.expires
+    }
+
+    fn set_expiry(&mut self,round:Option<usize>) {
+        self.expires = round;
+    }
+
+    fn box_clone(&self) -> Box<dyn Fix<D>> {
+        Box::new(self.clone())
+    }
+}
+
+/// F3: node `edge.0` can no longer delegate to node `edge.1`
+#[derive(Clone,Debug,PartialEq)]
+pub struct F3Fix {
+    pub edge: (usize,usize),
+    // `qbot_function_5` score
+    pub score: f32,
+    pub expires: Option<usize>
+}
+
+impl<D:AnsDomain> Fix<D> for F3Fix {
+
+    fn name(&self) -> String {
+        format!("F3 edge {} -> {}",self.edge.0,self.edge.1)
+    }
+
+    fn fuel_action(&self) -> fuel::FuelAction {
+        fuel::FuelAction::F3
+    }
+
+    fn target(&self) -> Option<usize> {
+        Some(self.edge.0)
+    }
+
+    fn score(&self) -> i32 {
+        self.score.round() as i32
+    }
+
+    /// edge exists and has no dead node
+    fn applicable(&self,env:&mut RNBENV<D>) -> bool {
+        let (a,b) = self.edge;
+        let l = env.fetch_network().nodes.len();
+        if a >= l || b >= l || !env.fetch_network().nodes[a].neighbors.contains(&b) {
+            return false;
+        }
+        env.node_state(a).is_alive() && env.node_state(b).is_alive()
+    }
+
+    fn apply(&mut self,env:&mut RNBENV<D>) {
+        let (a,b) = self.edge;
+        env.fetch_network().nodes[a].delete_neighbor(b);
+        env.fetch_QStruct().severed.insert(self.edge);
+    }
+
+    fn revert(&mut self,env:&mut RNBENV<D>) {
+        let (a,b) = self.edge;
+        let n = &mut env.fetch_network().nodes[a];
+        if !n.neighbors.contains(&b) {
+            n.neighbors.push(b);
+        }
+        env.fetch_QStruct().severed.remove(&self.edge);
+    }
+
+    fn restricted(&self) -> Option<usize> {
+        Some(self.edge.1)
+    }
+
+    fn remap_nodes(&mut self,f:&dyn Fn(usize) -> Option<usize>) -> bool {
+        let e = f(self.edge.0).zip(f(self.edge.1));
+        e.map(|x| self.edge = x).is_some()
+    }
+
+    fn expiry(&self) -> Option<usize> {
+        self.expires
+    }
+
+    fn set_expiry(&mut self,round:Option<usize>) {
+        self.expires = round;
+    }
+
+    fn box_clone(&self) -> Box<dyn Fix<D>> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rnb_env;
+
+    /// node answers `ans` and its delegates are not logged until `until`
+    #[derive(Clone)]
+    struct QuarantineFix {
+        node: usize,
+        ans: i32,
+        until: usize
+    }
+
+    impl Fix<(i32,i32)> for QuarantineFix {
+
+        fn name(&self) -> String {
+            format!("quarantine node {}",self.node)
+        }
+
+        fn fuel_action(&self) -> fuel::FuelAction {
+            fuel::FuelAction::F1
+        }
+
+        fn target(&self) -> Option<usize> {
+            Some(self.node)
+        }
+
+        fn score(&self) -> i32 {
+            3
+        }
+
+        fn applicable(&self,env:&mut RNBENV) -> bool {
+            !env.fetch_QStruct().dead_nodes.contains(&self.node)
+        }
+
+        fn apply(&mut self,_env:&mut RNBENV) {}
+
+        fn expiry(&self) -> Option<usize> {
+            Some(self.until)
+        }
+
+        fn answer(&mut self,ni:usize,_qi:usize) -> Option<i32> {
+            if ni == self.node {Some(self.ans)} else {None}
+        }
+
+        fn filter_log(&self,srcidn:usize,answers:&mut HashMap<usize,i32>) {
+            if srcidn == self.node {
+                answers.retain(|k,_| *k == srcidn);
+            }
+        }
+
+        fn box_clone(&self) -> Box<dyn Fix<(i32,i32)>> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test__Fix_registry() {
+        let mut r = rnb_env::sample_RNBENV1();
+        r.fetch_QStruct().fuel.f1 = fuel::CostRule::Proportional{rate:1.,min:1};
+        let c = r.fetch_QStruct().c;
+
+        // new fix without changes to RNBENV
+        assert!(r.apply_fix(Box::new(QuarantineFix{node:4,ans:7,until:2})));
+        assert_eq!(r.fetch_QStruct().c,c - 3);
+        assert_eq!(r.fixes.len(),1);
+        assert_eq!(r.fixes[0].fix.name(),"quarantine node 4");
+        assert_eq!(r.node_ans_to_q(4,0),7);
+        // answer is kept in the answer domain
+        assert_eq!(r.node_ans_to_q(4,3),0);
+
+        // node 4 answers directly
+        let s = crate::node_agent::ScriptedNode{answers:HashMap::new(),delegate:Some(false),only:None};
+        r.controllers.insert(crate::id_registry::NodeId::Num(4),Box::new(s));
+        r.execute_query_on_node(4,0,false);
+        assert_eq!(r.fetch_QStruct().rd.z[[4,0]],7);
+
+        // copies of environment keep the fixes
+        let mut r2 = r.clone_sim();
+        assert_eq!(r2.node_ans_to_q(4,0),7);
+
+        // fix expires
+        r.new_round(1);
+        assert_eq!(r.fixes.len(),1);
+        r.new_round(2);
+        assert!(r.fixes.is_empty());
+        assert!(r.fix_answer(4,0).is_none());
+    }
+
+    #[test]
+    fn test__Fix_F1_answer() {
+        let mut r = rnb_env::sample_RNBENV1();
+        r.f1_src = rnode::F1Source::QDictated;
+        r.fetch_network().nodes[3].resistance = -1.;
+        assert!(r.fix_F1_node(3));
+
+        // answers come from the pattern of the registry, not the node
+        assert!(r.is_F1_fixed(3));
+        let a = r.fetch_QStruct().ans_to_q(0);
+        let x = r.fixes[0].fix.answer(3,0);
+        assert_eq!(x,Some(a));
+        assert_eq!(r.node_ans_to_q(3,0),a);
+        assert!(r.fix_answer(4,0).is_none());
+
+        r.revert_fix(0);
+        assert!(!r.is_F1_fixed(3));
+        assert!(r.fix_answer(3,0).is_none());
+    }
+
+    #[test]
+    fn test__Fix_F2_revert() {
+        let mut r = rnb_env::sample_RNBENV1();
+        let b = r.fetch_network().nodes[0].neighbors[0];
+        r.fetch_QStruct().rd.w[[b,0]] = 1;
+
+        // F2 on b bars it from answering for node 0
+        assert!(r.fix_F2(Some((b,2)),false));
+        assert!(!r.fix_allows_delegate(b));
+        r.node_delegation(0,0,false);
+        assert!(!r.fetch_node(0).db.delegation_path.as_ref().unwrap().na.contains_key(&b));
+
+        let x = r.revert_fix(0);
+        assert_eq!(x.fix.name(),format!("F2 node {}",b));
+        assert!(r.fix_allows_delegate(b));
+        assert!(r.fetch_QStruct().f2_nodes.is_empty());
+        r.node_delegation(0,0,false);
+        assert!(r.fetch_node(0).db.delegation_path.as_ref().unwrap().na.contains_key(&b));
+
+        // fixes on a removed node are dropped, others are moved
+        r.fetch_QStruct().rd.w[[5,0]] = 1;
+        r.fetch_QStruct().rd.w[[7,0]] = 1;
+        assert!(r.fix_F2(Some((5,1)),false));
+        assert!(r.fix_F2(Some((7,1)),false));
+        r.remove_node(&crate::id_registry::NodeId::Num(5));
+        assert_eq!(r.fixes.len(),1);
+        assert!(!r.fix_allows_delegate(6));
+        assert_eq!(r.fetch_QStruct().f2_nodes,std::collections::HashSet::from_iter(vec![6]));
+    }
+}
@@ -0,0 +1,221 @@
+//! fuel economy of QStruct.
+//! Each action of Q (query, F1 fix, F2 fix) has a cost rule; fuel may
+//! regenerate per round, and every fuel transaction is logged.
+use crate::id_registry::NodeId;
+use std::fmt;
+
+/// action of Q that changes its fuel
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum FuelAction {
+    Query,
+    F1,
+    F2,
+    F3,
+    Regen
+}
+
+/// cost of an action given its score (F2: `qbot_function_1` score,
+/// F3: `qbot_function_5` score)
+#[derive(Clone,Debug)]
+pub enum CostRule {
+    /// constant cost regardless of score
+    Fixed(i32),
+    /// rate * |score|, at least `min`
+    Proportional{rate:f32,min:i32},
+    /// rate * |score| bounded by [min,max]
+    Capped{rate:f32,min:i32,max:i32}
+}
+
+impl CostRule {
+
+    /// cost of action with score; never negative, so an action
+    /// cannot refund fuel.
+    pub fn cost(&self,score:i32) -> i32 {
+        let c = match self {
+            CostRule::Fixed(x) => *x,
+            CostRule::Proportional{rate,min} => ((*rate * score.abs() as f32).round() as i32).max(*min),
+            CostRule::Capped{rate,min,max} => ((*rate * score.abs() as f32).round() as i32).max(*min).min(*max)
+        };
+        c.max(0)
+    }
+}
+
+/// one change of fuel
+#[derive(Clone,Debug,PartialEq)]
+pub struct FuelTransaction {
+    pub round: usize,
+    pub action: FuelAction,
+    // external id of target node of action; dense indices change when
+    // nodes join or leave the network
+    pub node: Option<NodeId>,
+    // signed change of fuel; negative for a spend
+    pub delta: i32,
+    // fuel after the transaction
+    pub fuel: i32,
+    // false if action was refused for lack of fuel
+    pub accepted: bool
+}
+
+#[derive(Clone)]
+pub struct FuelModel {
+    pub query: CostRule,
+    pub f1: CostRule,
+    pub f2: CostRule,
+    pub f3: CostRule,
+
+    // fuel added at the start of each round, up to max_fuel if any
+    pub regen: i32,
+    pub max_fuel: Option<i32>,
+
+    // refuse actions that cost more than the fuel left
+    pub refuse_unaffordable: bool,
+
+    pub round: usize,
+    pub log: Vec<FuelTransaction>
+}
+
+/// default: query, F1 and F3 cost 1, F2 costs its score but at least 1,
+/// no regeneration and no refusal.
+pub fn build_FuelModel() -> FuelModel {
+    FuelModel{query:CostRule::Fixed(1),f1:CostRule::Fixed(1),
+        f2:CostRule::Proportional{rate:1.,min:1},f3:CostRule::Fixed(1),regen:0,max_fuel:None,
+        refuse_unaffordable:false,round:0,log:Vec::new()}
+}
+
+impl FuelModel {
+
+    pub fn cost(&self,action:FuelAction,score:i32) -> i32 {
+        match action {
+            FuelAction::Query => self.query.cost(score),
+            FuelAction::F1 => self.f1.cost(score),
+            FuelAction::F2 => self.f2.cost(score),
+            FuelAction::F3 => self.f3.cost(score),
+            FuelAction::Regen => 0
+        }
+    }
+
+    /// # description
+    /// if Q with fuel `fuel` can take action with score; `reserve` is fuel
+    /// that has to remain after the action.
+    pub fn can_afford(&self,fuel:i32,action:FuelAction,score:i32,reserve:i32) -> bool {
+        !self.refuse_unaffordable || self.cost(action,score) + reserve <= fuel
+    }
+
+    /// # description
+    /// charges the cost of action on node to `fuel` and logs it.
+    /// # return
+    /// false if action is refused; fuel is then unchanged.
+    pub fn charge(&mut self,fuel:&mut i32,action:FuelAction,node:Option<NodeId>,score:i32) -> bool {
+        let c = self.cost(action,score);
+        let stat = self.can_afford(*fuel,action,score,0);
+        if stat {
+            *fuel -= c;
+        }
+
+        self.log.push(FuelTransaction{round:self.round,action:action,node:node,
+            delta:if stat {-c} else {0},fuel:*fuel,accepted:stat});
+        stat
+    }
+
+    /// logs action on node as refused with fuel `fuel` unchanged; for an
+    /// action Q does not take to keep a reserve.
+    pub fn refuse(&mut self,fuel:i32,action:FuelAction,node:Option<NodeId>) {
+        self.log.push(FuelTransaction{round:self.round,action:action,node:node,
+            delta:0,fuel:fuel,accepted:false});
+    }
+
+    /// # description
+    /// starts round `round` and regenerates `fuel`.
+    pub fn new_round(&mut self,round:usize,fuel:&mut i32) {
+        self.round = round;
+        if self.regen == 0 {
+            return;
+        }
+
+        let mut f = *fuel + self.regen;
+        if !self.max_fuel.is_none() {
+            f = f.min(self.max_fuel.unwrap().max(*fuel));
+        }
+        let d = f - *fuel;
+        *fuel = f;
+        self.log.push(FuelTransaction{round:round,action:FuelAction::Regen,node:None,
+            delta:d,fuel:f,accepted:true});
+    }
+
+    /// total fuel spent on action
+    pub fn spent(&self,action:FuelAction) -> i32 {
+        self.log.iter().filter(|x| x.action == action).map(|x| -x.delta).sum()
+    }
+
+    /// number of refused actions
+    pub fn refused(&self) -> usize {
+        self.log.iter().filter(|x| !x.accepted).count()
+    }
+}
+
+impl fmt::Display for FuelModel {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut q = format!("fuel transactions: {} refused: {}\n",self.log.len(),self.refused());
+        for a in vec![FuelAction::Query,FuelAction::F1,FuelAction::F2,FuelAction::F3].into_iter() {
+            q.push_str(&format!("{:?} spent {}\n",a,self.spent(a)));
+        }
+        q.push_str(&format!("Regen gained {}\n",-self.spent(FuelAction::Regen)));
+        write!(f, "{}", q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__CostRule_cost() {
+        assert_eq!(CostRule::Fixed(3).cost(-100),3);
+        assert_eq!(CostRule::Fixed(-3).cost(0),0);
+
+        let p = CostRule::Proportional{rate:0.5,min:1};
+        assert_eq!(p.cost(10),5);
+        assert_eq!(p.cost(-10),5);
+        assert_eq!(p.cost(0),1);
+
+        let c = CostRule::Capped{rate:2.,min:1,max:15};
+        assert_eq!(c.cost(3),6);
+        assert_eq!(c.cost(100),15);
+        assert_eq!(c.cost(0),1);
+    }
+
+    #[test]
+    fn test__FuelModel_charge() {
+        let mut m = build_FuelModel();
+        m.regen = 5;
+        m.max_fuel = Some(12);
+        let mut fuel = 10;
+
+        // F2 of negative score does not refund fuel
+        assert!(m.charge(&mut fuel,FuelAction::F2,Some(NodeId::Num(3)),-4));
+        assert_eq!(fuel,6);
+        assert!(m.charge(&mut fuel,FuelAction::F2,Some(NodeId::Num(4)),0));
+        assert_eq!(fuel,5);
+
+        m.new_round(1,&mut fuel);
+        assert_eq!(fuel,10);
+        m.new_round(2,&mut fuel);
+        assert_eq!(fuel,12);
+
+        // refusal leaves fuel unchanged
+        m.refuse_unaffordable = true;
+        assert!(!m.can_afford(fuel,FuelAction::F2,12,1));
+        assert!(!m.charge(&mut fuel,FuelAction::F2,Some(NodeId::Num(1)),20));
+        assert_eq!(fuel,12);
+        assert!(m.charge(&mut fuel,FuelAction::Query,Some(NodeId::Num(1)),0));
+        assert_eq!(fuel,11);
+
+        assert_eq!(m.log.len(),6);
+        assert_eq!(m.log[5],FuelTransaction{round:2,action:FuelAction::Query,node:Some(NodeId::Num(1)),
+            delta:-1,fuel:11,accepted:true});
+        assert_eq!(m.spent(FuelAction::F2),5);
+        assert_eq!(m.spent(FuelAction::Regen),-7);
+        assert_eq!(m.refused(),1);
+    }
+}
@@ -0,0 +1,301 @@
+//! gym-style interface of RNBENV for training policies of Q.
+//! An episode starts by `reset(seed)`; each `step(action)` executes one
+//! action of Q and outputs (observation, reward, done, info).
+use crate::rnb_env;
+use crate::std_rng;
+use crate::ans_domain::AnsDomain;
+use ndarray::Dim;
+use std::collections::HashSet;
+
+/// query of (node,question) and optional fixes applied before the query
+#[derive(Clone,Debug,PartialEq)]
+pub struct Action {
+    pub query: (usize,usize),
+    // node to fix by F1; node needs to have no resistance
+    pub f1: Option<usize>,
+    // node to fix by F2
+    pub f2: Option<usize>
+}
+
+/// discrete action space of queries; action i is (i / questions, i % questions)
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct ActionSpace {
+    pub nodes: usize,
+    pub questions: usize
+}
+
+impl ActionSpace {
+
+    pub fn n(&self) -> usize {
+        self.nodes * self.questions
+    }
+
+    pub fn decode(&self,i:usize) -> Action {
+        assert!(i < self.n());
+        Action{query:(i / self.questions,i % self.questions),f1:None,f2:None}
+    }
+}
+
+/// # description
+/// fixed-size encoding of the state known to Q, of length 4 * r * c + 1 + 2 * r
+/// for r nodes and c questions:
+/// W,X,Y (row-major), Z as the normalized distance of the mean answer of
+/// each node to Q's answer (0 for no answer), fuel, dead mask, F2 mask.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Observation {
+    pub shape: (usize,usize),
+    pub data: Vec<f32>
+}
+
+impl Observation {
+
+    pub fn fuel(&self) -> f32 {
+        let (r,c) = self.shape;
+        self.data[4 * r * c]
+    }
+
+    pub fn dead_mask(&self) -> &[f32] {
+        let (r,c) = self.shape;
+        &self.data[4 * r * c + 1..4 * r * c + 1 + r]
+    }
+
+    pub fn f2_mask(&self) -> &[f32] {
+        let (r,c) = self.shape;
+        &self.data[4 * r * c + 1 + r..]
+    }
+}
+
+/// reward of a step
+#[derive(Clone,Debug)]
+pub enum RewardFn {
+    /// negative fuel spent
+    FuelCost,
+    /// nodes neutralised (dead) per fuel spent
+    FuelEfficiency,
+    /// deceptive nodes neutralised: dead or fixed by F2
+    DeceptiveNeutralised,
+    /// fuel * fuel spent + dead * nodes dead + deceptive * deceptive nodes neutralised
+    Weighted{fuel:f32,dead:f32,deceptive:f32}
+}
+
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct StepInfo {
+    pub step: usize,
+    // false if action is not allowed; Q does nothing
+    pub valid: bool,
+    pub fuel_spent: i32,
+    pub new_dead: Vec<usize>,
+    pub new_f2: Vec<usize>,
+    pub f1_fixed: bool
+}
+
+pub struct GymEnv<D:AnsDomain = (i32,i32)> {
+    build: Box<dyn Fn() -> rnb_env::RNBENV<D>>,
+    pub env: rnb_env::RNBENV<D>,
+    pub reward: RewardFn,
+    // reward of an invalid action
+    pub invalid_reward: f32,
+    pub max_steps: usize,
+    pub steps: usize
+}
+
+pub fn build_GymEnv<D:AnsDomain>(build:Box<dyn Fn() -> rnb_env::RNBENV<D>>,reward:RewardFn,max_steps:usize) -> GymEnv<D> {
+    let e = build();
+    GymEnv{build:build,env:e,reward:reward,invalid_reward:-1.,max_steps:max_steps,steps:0}
+}
+
+impl<D:AnsDomain> GymEnv<D> {
+
+    /// # description
+    /// starts a new episode with random seed `seed`
+    pub fn reset(&mut self,seed:u64) -> Observation {
+        std_rng::set_seed(seed);
+        self.env = (self.build)();
+        self.steps = 0;
+        self.observe()
+    }
+
+    pub fn action_space(&mut self) -> ActionSpace {
+        let q = self.env.fetch_QStruct();
+        let (r,c) = q.rd.x.dim();
+        ActionSpace{nodes:r,questions:c}
+    }
+
+    pub fn observe(&mut self) -> Observation {
+        let q = self.env.fetch_QStruct();
+        let (r,c) = q.rd.x.dim();
+        let mut v: Vec<f32> = Vec::with_capacity(4 * r * c + 1 + 2 * r);
+        v.extend(q.rd.w.iter().map(|x| *x as f32));
+        v.extend(q.rd.x.iter().cloned());
+        v.extend(q.rd.y.iter().map(|x| *x as f32));
+
+        let av = q.ans_vec();
+        for i in 0..r {
+            for j in 0..c {
+                let d = Dim((i,j));
+                if q.rd.y[d] == 0 {
+                    v.push(0.);
+                    continue;
+                }
+                let dom = &q.qs[j].ans_range;
+                v.push(dom.normalize(dom.distance(&q.rd.z[d],&av[j])));
+            }
+        }
+
+        v.push(q.c as f32);
+        v.extend((0..r).into_iter().map(|i| if q.dead_nodes.contains(&i) {1.} else {0.}));
+        v.extend((0..r).into_iter().map(|i| if q.f2_nodes.contains(&i) {1.} else {0.}));
+        Observation{shape:(r,c),data:v}
+    }
+
+    /// # description
+    /// if `action` is allowed: nodes and questions exist, the queried node
+    /// is alive and the question is not retired.
+    pub fn is_valid(&mut self,action:&Action) -> bool {
+        let q = self.env.fetch_QStruct();
+        let (r,c) = q.rd.x.dim();
+        let (ni,qi) = action.query;
+        if ni >= r || qi >= c || q.dead_nodes.contains(&ni) || q.retired_qs.contains(&qi) {
+            return false;
+        }
+        action.f1.map_or(true,|x| x < r) && action.f2.map_or(true,|x| x < r && !q.f2_nodes.contains(&x))
+    }
+
+    /// # description
+    /// executes `action` of Q.
+    /// # return
+    /// (observation,reward,done,info)
+    pub fn step(&mut self,action:Action) -> (Observation,f32,bool,StepInfo) {
+        let mut info = StepInfo{step:self.steps,..Default::default()};
+        self.steps += 1;
+
+        if !self.is_valid(&action) {
+            let d = self.is_done();
+            return (self.observe(),self.invalid_reward,d,info);
+        }
+        info.valid = true;
+
+        self.env.new_round(info.step);
+        let q = self.env.fetch_QStruct();
+        let c0 = q.c;
+        let dead0 = q.dead_nodes.clone();
+        let f20 = q.f2_nodes.clone();
+
+        if !action.f1.is_none() {
+            info.f1_fixed = self.env.fix_F1_node(action.f1.unwrap());
+        }
+        let f2 = action.f2.map(|x| (x,self.env.fetch_QStruct().F2_score(x)));
+        self.env.execute_planned_Q_move(Some(action.query),f2,false);
+
+        let q = self.env.fetch_QStruct();
+        info.fuel_spent = c0 - q.c;
+        info.new_dead = q.dead_nodes.difference(&dead0).cloned().collect();
+        info.new_f2 = q.f2_nodes.difference(&f20).cloned().collect();
+        info.new_dead.sort();
+        info.new_f2.sort();
+
+        let rw = self.reward_of(&info);
+        let d = self.is_done();
+        (self.observe(),rw,d,info)
+    }
+
+    /// # description
+    /// episode ends when Q is out of fuel, every node is dead or the
+    /// step limit is reached.
+    pub fn is_done(&mut self) -> bool {
+        let s = self.steps >= self.max_steps;
+        let q = self.env.fetch_QStruct();
+        s || q.c <= 0 || q.dead_nodes.len() >= q.rd.x.dim().0
+    }
+
+    /// number of nodes of `v` with a deceptive objective for any question
+    fn deceptive_count(&mut self,v:&Vec<usize>) -> usize {
+        v.iter().filter(|x| self.env.fetch_node(**x).db.obj.values().any(|o| *o == 1)).count()
+    }
+
+    pub fn reward_of(&mut self,info:&StepInfo) -> f32 {
+        let mut n:HashSet<usize> = info.new_dead.iter().cloned().collect();
+        n.extend(info.new_f2.iter().cloned());
+        let dc = self.deceptive_count(&n.into_iter().collect()) as f32;
+        let f = info.fuel_spent as f32;
+        let dn = info.new_dead.len() as f32;
+
+        match self.reward {
+            RewardFn::FuelCost => -f,
+            RewardFn::FuelEfficiency => dn / f.max(1.),
+            RewardFn::DeceptiveNeutralised => dc,
+            RewardFn::Weighted{fuel,dead,deceptive} => fuel * f + dead * dn + deceptive * dc
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_GymEnv1(reward:RewardFn) -> GymEnv {
+        build_GymEnv(Box::new(rnb_env::sample_RNBENV1),reward,20)
+    }
+
+    #[test]
+    fn test__GymEnv_reset_step() {
+        let mut g = sample_GymEnv1(RewardFn::FuelCost);
+        let o = g.reset(3);
+        assert_eq!(o.shape,(11,5));
+        assert_eq!(o.data.len(),4 * 55 + 1 + 22);
+        assert_eq!(o.fuel(),2000.);
+        assert_eq!(g.action_space().n(),55);
+        assert_eq!(g.action_space().decode(12).query,(2,2));
+
+        // query node 2 question 0, deceptive answer
+        let (o,r,d,info) = g.step(Action{query:(2,0),f1:None,f2:None});
+        assert!(info.valid && !d);
+        assert_eq!(info.fuel_spent,1);
+        assert_eq!(r,-1.);
+        assert_eq!(o.fuel(),1999.);
+        assert_eq!(o.data[2 * 55 + 10],1.);
+
+        // invalid actions
+        let (_,r,_,info) = g.step(Action{query:(11,0),f1:None,f2:None});
+        assert!(!info.valid);
+        assert_eq!(r,-1.);
+
+        // F2 fix of a node that was never a delegate is not charged
+        let (_,_,_,info) = g.step(Action{query:(0,0),f1:None,f2:Some(3)});
+        assert!(info.new_f2.is_empty());
+        assert_eq!(info.fuel_spent,1);
+
+        // F2 fix of a deceptive delegate
+        g.env.fetch_QStruct().rd.w[[3,0]] = 2;
+        g.reward = RewardFn::DeceptiveNeutralised;
+        let (o,r,_,info) = g.step(Action{query:(0,0),f1:None,f2:Some(3)});
+        assert_eq!(info.new_f2,vec![3]);
+        assert_eq!(r,1.);
+        assert_eq!(o.f2_mask()[3],1.);
+    }
+
+    #[test]
+    fn test__GymEnv_reset_seed() {
+        let mut g = sample_GymEnv1(RewardFn::Weighted{fuel:-0.01,dead:1.,deceptive:1.});
+        let run = |g:&mut GymEnv| -> Vec<Observation> {
+            let mut v = vec![g.reset(7)];
+            let s = g.action_space();
+            for i in 0..10 {
+                let (o,_,_,_) = g.step(s.decode((i * 7) % s.n()));
+                v.push(o);
+            }
+            v
+        };
+        let v1 = run(&mut g);
+        let v2 = run(&mut g);
+        assert!(v1 == v2);
+
+        // episode ends at the step limit
+        let mut d = false;
+        for _ in 0..20 {
+            let (_,_,d2,_) = g.step(Action{query:(0,1),f1:None,f2:None});
+            d = d2;
+        }
+        assert!(d);
+    }
+}
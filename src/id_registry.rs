@@ -0,0 +1,131 @@
+//! registry of external node identifiers.
+//! Nodes are identified externally by a number or a name (e.g. from
+//! scenario files); internally a node's idn is its dense index, which is
+//! its position in RNetwork.nodes and its row in QData.
+use std::collections::HashMap;
+use std::fmt;
+
+/// external identifier of a node
+#[derive(Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub enum NodeId {
+    Num(u64),
+    Name(String)
+}
+
+impl From<u64> for NodeId {
+    fn from(x:u64) -> NodeId {
+        NodeId::Num(x)
+    }
+}
+
+impl From<usize> for NodeId {
+    fn from(x:usize) -> NodeId {
+        NodeId::Num(x as u64)
+    }
+}
+
+impl From<&str> for NodeId {
+    fn from(x:&str) -> NodeId {
+        NodeId::Name(x.to_string())
+    }
+}
+
+impl fmt::Display for NodeId {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeId::Num(x) => write!(f, "{}", x),
+            NodeId::Name(x) => write!(f, "{}", x)
+        }
+    }
+}
+
+/// two-way map of external ids and dense indices 0..n-1
+#[derive(Clone,Debug)]
+pub struct IdRegistry {
+    pub ids: Vec<NodeId>,
+    pub index: HashMap<NodeId,usize>
+}
+
+pub fn build_IdRegistry() -> IdRegistry {
+    IdRegistry{ids:Vec::new(),index:HashMap::new()}
+}
+
+/// registry of ids in sorted order, so that indices do not depend on
+/// the iteration order of a HashMap.
+pub fn build_IdRegistry_sorted(mut v:Vec<NodeId>) -> IdRegistry {
+    v.sort();
+    v.dedup();
+    let mut r = build_IdRegistry();
+    for x in v.into_iter() {
+        r.register(x);
+    }
+    r
+}
+
+impl IdRegistry {
+
+    /// # description
+    /// dense index of id; registers id at the next index if new.
+    pub fn register(&mut self,id:NodeId) -> usize {
+        if self.index.contains_key(&id) {
+            return self.index[&id];
+        }
+        let i = self.ids.len();
+        self.index.insert(id.clone(),i);
+        self.ids.push(id);
+        i
+    }
+
+    /// # description
+    /// removes the id of index i; ids of greater index move down by one.
+    pub fn remove(&mut self,i:usize) -> NodeId {
+        let x = self.ids.remove(i);
+        self.index = self.ids.iter().cloned().enumerate().map(|(j,y)| (y,j)).collect();
+        x
+    }
+
+    pub fn idx(&self,id:&NodeId) -> Option<usize> {
+        self.index.get(id).cloned()
+    }
+
+    pub fn id(&self,i:usize) -> &NodeId {
+        &self.ids[i]
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// # description
+    /// map of dense indices to map of external ids
+    pub fn external_keys<V>(&self,h:HashMap<usize,V>) -> HashMap<NodeId,V> {
+        h.into_iter().map(|(k,v)| (self.ids[k].clone(),v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__IdRegistry() {
+        let v:Vec<NodeId> = vec!["b".into(),NodeId::Num(12),"a".into(),NodeId::Num(3),NodeId::Num(12)];
+        let mut r = build_IdRegistry_sorted(v);
+        assert_eq!(r.len(),4);
+        assert_eq!(r.ids,vec![NodeId::Num(3),NodeId::Num(12),"a".into(),"b".into()]);
+        assert_eq!(r.idx(&"a".into()),Some(2));
+        assert_eq!(r.idx(&"c".into()),None);
+        assert_eq!(r.register("c".into()),4);
+        assert_eq!(r.register(NodeId::Num(12)),1);
+        assert_eq!(format!("{}",r.id(4)),"c");
+
+        assert_eq!(r.remove(1),NodeId::Num(12));
+        assert_eq!(r.idx(&"c".into()),Some(3));
+        assert_eq!(r.idx(&NodeId::Num(12)),None);
+        assert_eq!(r.register(NodeId::Num(12)),4);
+
+        let h = r.external_keys(HashMap::from_iter(vec![(1,0.5)]));
+        assert_eq!(h[&"a".into()],0.5);
+    }
+}
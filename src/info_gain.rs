@@ -0,0 +1,184 @@
+//! information-gain selection of Q's queries.
+//! Q keeps a posterior over the objective of each (node,question) pair
+//! from the contradiction of the node's direct answers, and queries the
+//! pair of maximal expected information gain per unit of fuel. The gain
+//! adds the uncertainty of Q about unknown answers that an honest node
+//! would reduce.
+use crate::q_struct;
+use crate::ans_domain::AnsDomain;
+use crate::fuel;
+
+/// number of objectives: honest, deceptive, erratic; see `classifier::NodeLabel`
+pub const NUM_OBJECTIVES: usize = 3;
+/// number of bins of contradiction: [0,1/3), [1/3,2/3), [2/3,1]
+pub const NUM_BINS: usize = 3;
+
+/// model of answers by objective
+#[derive(Clone,Debug,PartialEq)]
+pub struct InfoGainModel {
+    // prior of each objective
+    pub prior: [f32;NUM_OBJECTIVES],
+    // probability of each bin of contradiction by objective
+    pub likelihood: [[f32;NUM_BINS];NUM_OBJECTIVES],
+    // bits of gain per unit of uncertainty of an unknown answer
+    pub ans_weight: f32
+}
+
+pub fn build_InfoGainModel() -> InfoGainModel {
+    InfoGainModel{prior:[0.6,0.3,0.1],
+        likelihood:[[0.8,0.15,0.05],[0.05,0.15,0.8],[1. / 3.,1. / 3.,1. / 3.]],
+        ans_weight:1.}
+}
+
+/// policy of choosing the (node,question) pair of a query
+#[derive(Clone,Debug,PartialEq)]
+pub enum NQSelector {
+    /// `QStruct::priority_nq_pair`
+    Priority,
+    /// maximal expected information gain per unit of fuel
+    InfoGain(InfoGainModel)
+}
+
+pub fn contradiction_bin(c:f32) -> usize {
+    ((c.max(0.) * NUM_BINS as f32) as usize).min(NUM_BINS - 1)
+}
+
+/// entropy in bits of distribution `p`
+pub fn entropy(p:&[f32]) -> f32 {
+    p.iter().filter(|x| **x > 0.).map(|x| -x * x.log2()).sum()
+}
+
+fn normalized(p:[f32;NUM_OBJECTIVES]) -> [f32;NUM_OBJECTIVES] {
+    let s:f32 = p.iter().sum();
+    if s == 0. {
+        return [1. / NUM_OBJECTIVES as f32;NUM_OBJECTIVES];
+    }
+    let mut x = p;
+    x.iter_mut().for_each(|y| *y /= s);
+    x
+}
+
+impl InfoGainModel {
+
+    /// posterior over objectives after the contradictions `cs`
+    pub fn posterior(&self,cs:&Vec<f32>) -> [f32;NUM_OBJECTIVES] {
+        let mut p = self.prior;
+        for c in cs.iter() {
+            let b = contradiction_bin(*c);
+            for o in 0..NUM_OBJECTIVES {
+                p[o] *= self.likelihood[o][b];
+            }
+            p = normalized(p);
+        }
+        normalized(p)
+    }
+
+    /// # description
+    /// expected decrease of the entropy of posterior `p` from one more
+    /// answer of the node.
+    pub fn expected_gain(&self,p:&[f32;NUM_OBJECTIVES]) -> f32 {
+        let h = entropy(p);
+        let mut eh = 0.;
+        for b in 0..NUM_BINS {
+            let mut p2 = *p;
+            for o in 0..NUM_OBJECTIVES {
+                p2[o] *= self.likelihood[o][b];
+            }
+            let pb:f32 = p2.iter().sum();
+            if pb == 0. {
+                continue;
+            }
+            eh += pb * entropy(&normalized(p2));
+        }
+        (h - eh).max(0.)
+    }
+}
+
+/// # description
+/// contradictions of the direct answers of node `ni` to question `qi`
+/// against Q's answer.
+pub fn pair_contradictions<D:AnsDomain>(q:&mut q_struct::QStruct<D>,ni:usize,qi:usize) -> Vec<f32> {
+    if !q.rd.al.contains_key(&(ni,qi)) {
+        return Vec::new();
+    }
+    let a = q.ans_to_q(qi);
+    let dom = q.qs[qi].ans_range.clone();
+    q.rd.al[&(ni,qi)].iter().map(|x| dom.contradiction(&a,x)).collect()
+}
+
+/// # description
+/// expected information gain of querying node `ni` on question `qi`:
+/// gain on the objective of the pair, plus `ans_weight` * (1 - confidence
+/// of Q's answer) * probability that node is honest.
+pub fn pair_gain<D:AnsDomain>(m:&InfoGainModel,q:&mut q_struct::QStruct<D>,ni:usize,qi:usize) -> f32 {
+    let p = m.posterior(&pair_contradictions(q,ni,qi));
+    m.expected_gain(&p) + m.ans_weight * (1. - q.ans_confidence(qi)) * p[0]
+}
+
+/// # description
+/// (node,question) pair of maximal expected information gain per unit of
+/// fuel of a query; the first such pair for ties. None if Q has no pair
+/// to query.
+pub fn info_gain_nq_pair<D:AnsDomain>(m:&InfoGainModel,q:&mut q_struct::QStruct<D>) -> Option<(usize,usize)> {
+    let c = q.fuel.cost(fuel::FuelAction::Query,0).max(1) as f32;
+    let mut x: Option<((usize,usize),f32)> = None;
+    for (ni,qi) in q.candidate_nq_pairs().into_iter() {
+        let g = pair_gain(m,q,ni,qi) / c;
+        if x.is_none() || g > x.unwrap().1 {
+            x = Some(((ni,qi),g));
+        }
+    }
+    x.map(|y| y.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__InfoGainModel_posterior() {
+        let m = build_InfoGainModel();
+        assert_eq!(m.posterior(&Vec::new()),[0.6,0.3,0.1]);
+
+        // consistent high contradiction is deceptive
+        let p = m.posterior(&vec![0.9,0.95,1.]);
+        assert!(p[1] > 0.95);
+        // consistent low contradiction is honest
+        let p2 = m.posterior(&vec![0.,0.1,0.05]);
+        assert!(p2[0] > 0.95);
+
+        // an undecided pair has more to gain than a decided one
+        assert!(m.expected_gain(&[0.6,0.3,0.1]) > m.expected_gain(&p));
+        assert_eq!(m.expected_gain(&[1.,0.,0.]),0.);
+    }
+
+    #[test]
+    fn test__info_gain_nq_pair() {
+        let mut q = q_struct::sample_QStruct1();
+        q.selector = NQSelector::InfoGain(build_InfoGainModel());
+
+        // no data: question 1 without a known answer gains most
+        let x = q.select_nq_pair();
+        assert_eq!(x,Some((0,1)));
+
+        // node 0 answered question 1 three times, other pairs once
+        q.qs[1].qa = Some(40);
+        for _ in 0..3 {
+            q.rd.al.entry((0,1)).or_insert(Vec::new()).push(40);
+        }
+        for i in 0..11 {
+            for j in 0..5 {
+                if (i,j) != (0,1) {
+                    q.rd.al.insert((i,j),vec![q.qs[j].ans_range.1]);
+                }
+            }
+        }
+        q.dead_nodes.insert(1);
+        let x = q.select_nq_pair().unwrap();
+        assert!(x != (0,1) && x.0 != 1);
+
+        // no pair to query
+        q.dead_nodes = (0..11).collect();
+        assert!(q.select_nq_pair().is_none());
+    }
+}
@@ -0,0 +1,399 @@
+//! linear Q-learning agent of QStruct.
+//! The value of querying (node,question) is a linear function of features
+//! of the pair in the observation of gym::GymEnv; the agent is trained
+//! episode by episode over scenario seeds.
+use crate::gym;
+use crate::q_struct;
+use crate::rnetwork;
+use crate::rndb;
+use crate::rnb_env;
+use crate::std_rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+pub const NUM_FEATURES: usize = 7;
+
+/// # description
+/// features of (node,question) pair in observation `o` with initial fuel `fuel0`:
+/// bias, contradiction, ln(1 + y), ln(1 + w), normalized distance of mean
+/// answer to Q's answer, unasked indicator, fraction of fuel left.
+pub fn pair_features(o:&gym::Observation,ni:usize,qi:usize,fuel0:f32) -> [f32;NUM_FEATURES] {
+    let (r,c) = o.shape;
+    let k = ni * c + qi;
+    let w = o.data[k];
+    let x = o.data[r * c + k];
+    let y = o.data[2 * r * c + k];
+    let z = o.data[3 * r * c + k];
+    [1.,x,(1. + y).ln(),(1. + w).ln(),z,if y == 0. {1.} else {0.},o.fuel() / fuel0.max(1.)]
+}
+
+/// result of one episode of a policy
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct EpisodeResult {
+    pub reward: f32,
+    pub fuel_spent: i32,
+    pub dead: usize,
+    pub steps: usize
+}
+
+#[derive(Clone,Debug)]
+pub struct LinearQ {
+    pub theta: Vec<f32>,
+    // learning rate, discount and exploration rate
+    pub alpha: f32,
+    pub gamma: f32,
+    pub epsilon: f32
+}
+
+pub fn build_LinearQ(alpha:f32,gamma:f32,epsilon:f32) -> LinearQ {
+    LinearQ{theta:vec![0.;NUM_FEATURES],alpha:alpha,gamma:gamma,epsilon:epsilon}
+}
+
+impl LinearQ {
+
+    pub fn value(&self,f:&[f32;NUM_FEATURES]) -> f32 {
+        self.theta.iter().zip(f.iter()).map(|(a,b)| a * b).sum()
+    }
+
+    /// # description
+    /// valid query of greatest value in observation `o`, with its features
+    /// and value; None if no query is valid.
+    pub fn best_action(&self,g:&mut gym::GymEnv,o:&gym::Observation,fuel0:f32) -> Option<(gym::Action,[f32;NUM_FEATURES],f32)> {
+        let (r,c) = o.shape;
+        let mut b: Option<(gym::Action,[f32;NUM_FEATURES],f32)> = None;
+        for i in 0..r {
+            for j in 0..c {
+                let a = gym::Action{query:(i,j),f1:None,f2:None};
+                if !g.is_valid(&a) {
+                    continue;
+                }
+                let f = pair_features(o,i,j,fuel0);
+                let v = self.value(&f);
+                if b.is_none() || v > b.as_ref().unwrap().2 {
+                    b = Some((a,f,v));
+                }
+            }
+        }
+        b
+    }
+
+    /// # description
+    /// epsilon-greedy choice among the valid queries of `o`
+    pub fn choose(&self,g:&mut gym::GymEnv,o:&gym::Observation,fuel0:f32) -> Option<(gym::Action,[f32;NUM_FEATURES])> {
+        if std_rng::random_f32_in_range((0.,1.)) >= self.epsilon {
+            return self.best_action(g,o,fuel0).map(|x| (x.0,x.1));
+        }
+
+        let (r,c) = o.shape;
+        let v:Vec<gym::Action> = (0..r * c).into_iter().map(|i| gym::Action{query:(i / c,i % c),f1:None,f2:None})
+            .filter(|a| g.is_valid(a)).collect();
+        if v.len() == 0 {
+            return None;
+        }
+        let i = std_rng::random_i32_in_range((0,v.len() as i32 - 1)) as usize;
+        let (ni,qi) = v[i].query;
+        Some((v[i].clone(),pair_features(o,ni,qi,fuel0)))
+    }
+
+    /// # description
+    /// trains on one episode of scenario seed `seed` by Q-learning
+    /// # return
+    /// total reward of episode
+    pub fn train_episode(&mut self,g:&mut gym::GymEnv,seed:u64) -> f32 {
+        let mut o = g.reset(seed);
+        let fuel0 = o.fuel();
+        let mut t = 0.;
+        loop {
+            let x = self.choose(g,&o,fuel0);
+            if x.is_none() {
+                break;
+            }
+            let (mut a,f) = x.unwrap();
+            a.f2 = builtin_F2(g);
+            let (o2,rw,d,_) = g.step(a);
+            t += rw;
+
+            // target of update
+            let mut y = rw;
+            if !d {
+                y += self.gamma * self.best_action(g,&o2,fuel0).map_or(0.,|b| b.2);
+            }
+            let e = y - self.value(&f);
+            for k in 0..NUM_FEATURES {
+                self.theta[k] += self.alpha * e * f[k];
+            }
+
+            o = o2;
+            if d {
+                break;
+            }
+        }
+        t
+    }
+
+    /// total reward of each training episode over `seeds`
+    pub fn train(&mut self,g:&mut gym::GymEnv,seeds:&Vec<u64>) -> Vec<f32> {
+        seeds.iter().map(|s| self.train_episode(g,*s)).collect()
+    }
+
+    /// # description
+    /// runs the greedy policy on one episode of scenario seed `seed`; the
+    /// policy chooses the query and `builtin_F2` the F2 fix.
+    pub fn run_episode(&self,g:&mut gym::GymEnv,seed:u64) -> EpisodeResult {
+        let mut o = g.reset(seed);
+        let fuel0 = o.fuel();
+        let mut e = EpisodeResult::default();
+        loop {
+            let x = self.best_action(g,&o,fuel0);
+            if x.is_none() {
+                break;
+            }
+            let mut a = x.unwrap().0;
+            a.f2 = builtin_F2(g);
+            let (o2,rw,d,info) = g.step(a);
+            e.add(rw,&info);
+            o = o2;
+            if d {
+                break;
+            }
+        }
+        e
+    }
+
+    /// # description
+    /// policy in text format:
+    /// `linear-q <alpha> <gamma> <epsilon>` then the weights on one line
+    pub fn to_text(&self) -> String {
+        let t:Vec<String> = self.theta.iter().map(|x| x.to_string()).collect();
+        format!("linear-q {} {} {}\n{}\n",self.alpha,self.gamma,self.epsilon,t.join(" "))
+    }
+
+    pub fn save(&self,path:&str) -> std::io::Result<()> {
+        fs::write(path,self.to_text())
+    }
+}
+
+impl EpisodeResult {
+
+    pub fn add(&mut self,reward:f32,info:&gym::StepInfo) {
+        self.reward += reward;
+        self.fuel_spent += info.fuel_spent;
+        self.dead += info.new_dead.len();
+        self.steps += 1;
+    }
+}
+
+/// # description
+/// policy from text of `LinearQ::to_text`; None if text is malformed
+pub fn linear_q_from_text(s:&str) -> Option<LinearQ> {
+    let mut l = s.lines();
+    let h:Vec<&str> = l.next()?.split_whitespace().collect();
+    if h.len() != 4 || h[0] != "linear-q" {
+        return None;
+    }
+    let p:Vec<f32> = h[1..].iter().map(|x| x.parse::<f32>().ok()).collect::<Option<Vec<f32>>>()?;
+    let t:Vec<f32> = l.next()?.split_whitespace().map(|x| x.parse::<f32>().ok()).collect::<Option<Vec<f32>>>()?;
+    if t.len() != NUM_FEATURES {
+        return None;
+    }
+    Some(LinearQ{theta:t,alpha:p[0],gamma:p[1],epsilon:p[2]})
+}
+
+pub fn load_linear_q(path:&str) -> Option<LinearQ> {
+    linear_q_from_text(&fs::read_to_string(path).ok()?)
+}
+
+/// F2 fix of the built-in move of Q, `QStruct::F2_move`; the learned
+/// policy takes it with its query so both policies fix by F2 alike.
+pub fn builtin_F2(g:&mut gym::GymEnv) -> Option<usize> {
+    g.env.fetch_QStruct().F2_move().map(|x| x.0)
+}
+
+/// # description
+/// runs the built-in move of Q (`priority_nq_pair` and `qbot_function_1`)
+/// on one episode of scenario seed `seed`
+pub fn run_builtin_episode(g:&mut gym::GymEnv,seed:u64) -> EpisodeResult {
+    g.reset(seed);
+    let mut e = EpisodeResult::default();
+    loop {
+        let (i,i2) = g.env.plan_Q_move();
+        if i.is_none() {
+            break;
+        }
+        let (_,rw,d,info) = g.step(gym::Action{query:i.unwrap(),f1:None,f2:i2.map(|x| x.0)});
+        e.add(rw,&info);
+        if d {
+            break;
+        }
+    }
+    e
+}
+
+/// results of the learned and built-in policies over the same seeds
+pub struct PolicyComparison {
+    pub seeds: Vec<u64>,
+    pub learned: Vec<EpisodeResult>,
+    pub builtin: Vec<EpisodeResult>
+}
+
+/// mean (reward,fuel spent,dead) of results
+pub fn mean_result(v:&Vec<EpisodeResult>) -> (f32,f32,f32) {
+    let n = v.len().max(1) as f32;
+    (v.iter().map(|x| x.reward).sum::<f32>() / n,
+    v.iter().map(|x| x.fuel_spent as f32).sum::<f32>() / n,
+    v.iter().map(|x| x.dead as f32).sum::<f32>() / n)
+}
+
+impl fmt::Display for PolicyComparison {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let l = mean_result(&self.learned);
+        let b = mean_result(&self.builtin);
+        write!(f, "episodes {}\nlearned: reward {} fuel {} dead {}\nbuilt-in: reward {} fuel {} dead {}\n",
+            self.seeds.len(),l.0,l.1,l.2,b.0,b.1,b.2)
+    }
+}
+
+pub fn compare_policies(p:&LinearQ,g:&mut gym::GymEnv,seeds:&Vec<u64>) -> PolicyComparison {
+    let l = seeds.iter().map(|s| p.run_episode(g,*s)).collect();
+    let b = seeds.iter().map(|s| run_builtin_episode(g,*s)).collect();
+    PolicyComparison{seeds:seeds.clone(),learned:l,builtin:b}
+}
+
+/// # description
+/// random scenario of `n` nodes and `m` questions drawn from the random
+/// functions of `std_rng`: a ring with one random chord per node, Q knows
+/// every answer but that of question 0, and nodes are honest, deceptive or
+/// erratic with probability 0.6, 0.3, 0.1 for all questions.
+pub fn generated_RNBENV(n:usize,m:usize) -> rnb_env::RNBENV {
+    assert!(n >= 3 && m > 0);
+    let mut qs: Vec<q_struct::Q> = Vec::new();
+    let mut truth: Vec<i32> = Vec::new();
+    for j in 0..m {
+        let lo = std_rng::random_i32_in_range((-100,0));
+        let hi = lo + std_rng::random_i32_in_range((10,200));
+        let a = std_rng::random_i32_in_range((lo,hi));
+        truth.push(a);
+        qs.push(q_struct::Q{qa:if j == 0 {None} else {Some(a)},ans_range:(lo,hi)});
+    }
+
+    let mut nb: Vec<Vec<usize>> = (0..n).into_iter().map(|i| vec![(i + n - 1) % n,(i + 1) % n]).collect();
+    for i in 0..n {
+        let k = std_rng::random_i32_in_range((0,n as i32 - 1)) as usize;
+        if k != i && !nb[i].contains(&k) {
+            nb[i].push(k);
+            nb[k].push(i);
+        }
+    }
+
+    let mut m2: HashMap<usize,Vec<usize>> = HashMap::new();
+    let mut dbs: HashMap<usize,rndb::RNDB> = HashMap::new();
+    let mut rs: HashMap<usize,f32> = HashMap::new();
+    for i in 0..n {
+        let p = std_rng::random_f32_in_range((0.,1.));
+        let o = if p < 0.6 {0} else if p < 0.9 {1} else {2};
+        let ans = (0..m).into_iter().map(|j| (j,if std_rng::random_f32_in_range((0.,1.)) < 0.8 {Some(truth[j])} else {None})).collect();
+        let obj = (0..m).into_iter().map(|j| (j,o)).collect();
+        dbs.insert(i,rndb::build_RNDB(ans,obj));
+        m2.insert(i,nb[i].clone());
+        rs.insert(i,std_rng::random_f32_in_range((100.,200.)));
+    }
+
+    let mut e = rnb_env::build_RNBENV(q_struct::build_QStruct(qs,n,500),
+        rnetwork::build_RNetwork(m2,dbs,rs,1.));
+    e.ground_truth.insert(0,truth[0]);
+    e
+}
+
+/// # description
+/// trains a policy on `episodes` generated scenarios, saves it to `path`
+/// and compares it with the built-in move of Q on the sample scenario and
+/// on unseen generated scenarios.
+pub fn learn_and_evaluate(episodes:usize,path:&str) {
+    let r = gym::RewardFn::Weighted{fuel:-0.01,dead:1.,deceptive:1.};
+    let mut g = gym::build_GymEnv(Box::new(|| generated_RNBENV(12,4)),r,100);
+    let mut p = build_LinearQ(0.01,0.9,0.1);
+    let t = p.train(&mut g,&(0..episodes as u64).collect());
+    println!("training reward: first {:?} last {:?}",t.first(),t.last());
+    match p.save(path) {
+        Ok(_) => println!("policy saved to {}",path),
+        Err(e) => println!("policy not saved: {}",e)
+    }
+    evaluate(&p);
+}
+
+/// # description
+/// loads the policy saved at `path` and compares it with the built-in
+/// move of Q; see `evaluate`.
+pub fn load_and_evaluate(path:&str) {
+    match load_linear_q(path) {
+        Some(p) => evaluate(&p),
+        None => println!("no policy at {}",path)
+    }
+}
+
+/// # description
+/// compares policy `p` with the built-in move of Q on unseen generated
+/// scenarios and on the sample scenario.
+pub fn evaluate(p:&LinearQ) {
+    let r = gym::RewardFn::Weighted{fuel:-0.01,dead:1.,deceptive:1.};
+    let mut g = gym::build_GymEnv(Box::new(|| generated_RNBENV(12,4)),r.clone(),100);
+    let seeds:Vec<u64> = (1000..1020).collect();
+    println!("-- generated scenarios");
+    print!("{}",compare_policies(p,&mut g,&seeds));
+
+    let mut g2 = gym::build_GymEnv(Box::new(rnb_env::sample_RNBENV1),r,100);
+    println!("-- sample scenario");
+    print!("{}",compare_policies(p,&mut g2,&seeds));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__LinearQ_text() {
+        let mut p = build_LinearQ(0.5,0.9,0.1);
+        p.theta = vec![1.,-2.5,0.,3.,0.25,1.,-1.];
+        let p2 = linear_q_from_text(&p.to_text()).unwrap();
+        assert_eq!(p2.theta,p.theta);
+        assert_eq!((p2.alpha,p2.gamma,p2.epsilon),(0.5,0.9,0.1));
+        assert!(linear_q_from_text("linear-q 1 1\n0").is_none());
+        assert!(linear_q_from_text("linear-q 1 1 1\n0 1").is_none());
+
+        let f = std::env::temp_dir().join("test__LinearQ_text.policy");
+        let f = f.to_str().unwrap();
+        p.save(f).unwrap();
+        assert_eq!(load_linear_q(f).unwrap().theta,p.theta);
+        fs::remove_file(f).unwrap();
+        assert!(load_linear_q(f).is_none());
+    }
+
+    #[test]
+    fn test__LinearQ_train() {
+        let r = gym::RewardFn::Weighted{fuel:-0.01,dead:1.,deceptive:1.};
+        let mut g = gym::build_GymEnv(Box::new(|| generated_RNBENV(6,3)),r,30);
+
+        // generated scenario is fixed by the seed
+        let o1 = g.reset(4);
+        let o2 = g.reset(4);
+        assert_eq!(o1,o2);
+        assert_eq!(o1.shape,(6,3));
+
+        let mut p = build_LinearQ(0.01,0.9,0.2);
+        let t = p.train(&mut g,&vec![0,1,2]);
+        assert_eq!(t.len(),3);
+        assert!(p.theta.iter().all(|x| x.is_finite()));
+
+        let c = compare_policies(&p,&mut g,&vec![5,6]);
+        assert_eq!(c.learned.len(),2);
+        assert!(c.learned.iter().all(|x| x.steps > 0 && x.steps <= 30));
+        assert!(c.builtin.iter().all(|x| x.steps > 0));
+
+        // learned and built-in moves fix the same node by F2
+        g.reset(5);
+        let b = g.env.plan_Q_move().1.map(|x| x.0);
+        assert_eq!(builtin_F2(&mut g),b);
+    }
+}
@@ -9,13 +9,47 @@ mod df;
 mod rnetwork;
 mod rnb_env;
 mod ans;
+mod ans_domain;
 mod rnb_env_demo;
 
 mod qsbf; 
 mod f1pattern;
+mod f1detect;
+mod truth;
+mod est_metrics;
+mod classifier;
+mod fuel;
+mod id_registry;
+mod multi_q;
+mod collusion;
+mod gym;
+mod node_agent;
+mod learn_q;
+mod mcts;
+mod info_gain;
+mod fix;
+mod recovery;
+mod node_state;
 use ndarray::{arr1,arr2,Array1,Array2,Dim,s};
 
 fn main() {
+    ////
+    // `learn-q [episodes] [policy path]`: trains and evaluates the
+    // linear Q-learning agent
+    // `learn-q eval <policy path>`: evaluates a saved agent
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 2 && args[1] == "learn-q" && args[2] == "eval" {
+        let p = args.get(3).cloned().unwrap_or("linear_q.policy".to_string());
+        learn_q::load_and_evaluate(&p);
+        return;
+    }
+    if args.len() > 1 && args[1] == "learn-q" {
+        let e = args.get(2).and_then(|x| x.parse::<usize>().ok()).unwrap_or(200);
+        let p = args.get(3).cloned().unwrap_or("linear_q.policy".to_string());
+        learn_q::learn_and_evaluate(e,&p);
+        return;
+    }
+
     ////
     
     let mut r = rnb_env::sample_RNBENV1();
@@ -0,0 +1,203 @@
+//! Monte Carlo tree search planner of Q.
+//! Each iteration copies the environment, descends the tree of moves by
+//! UCT, expands one untried move and finishes with random moves up to the
+//! depth or fuel budget. The move of the root that is visited most is chosen.
+//! Planning is seeded and leaves the random state of the caller unchanged.
+use crate::rnb_env;
+use crate::std_rng;
+use crate::ans_domain::AnsDomain;
+use std::collections::HashSet;
+
+/// (node,question) query and F2 fix of a move; see `QStruct::one_move`
+pub type QMove = (Option<(usize,usize)>,Option<(usize,i32)>);
+
+#[derive(Clone,Debug)]
+pub struct MCTSConfig {
+    pub iterations: usize,
+    // maximum number of moves of a simulation
+    pub depth: usize,
+    // UCT exploration constant
+    pub exploration: f32,
+    pub seed: u64,
+    // fuel a simulation may spend; None for all of Q's fuel
+    pub fuel_budget: Option<i32>,
+    // outcome is fuel_weight * fuel left + dead_weight * dead nodes
+    // + deceptive_weight * deceptive nodes dead or fixed by F2
+    pub fuel_weight: f32,
+    pub dead_weight: f32,
+    pub deceptive_weight: f32
+}
+
+pub fn build_MCTSConfig(iterations:usize,depth:usize,seed:u64) -> MCTSConfig {
+    MCTSConfig{iterations:iterations,depth:depth,exploration:1.4,seed:seed,fuel_budget:None,
+        fuel_weight:0.01,dead_weight:1.,deceptive_weight:1.}
+}
+
+struct MCTSNode {
+    mv: QMove,
+    visits: usize,
+    value: f32,
+    children: Vec<usize>,
+    untried: Vec<QMove>
+}
+
+fn build_MCTSNode(mv:QMove,untried:Vec<QMove>) -> MCTSNode {
+    MCTSNode{mv:mv,visits:0,value:0.,children:Vec::new(),untried:untried}
+}
+
+/// random index below `n`
+fn random_index(n:usize) -> usize {
+    (std_rng::random_f32_in_range((0.,n as f32)) as usize).min(n - 1)
+}
+
+/// # description
+/// moves of Q in `e`: every valid query without a fix, and the move of
+/// `QStruct::one_move` with its F2 fix.
+pub fn candidate_moves<D:AnsDomain>(e:&mut rnb_env::RNBENV<D>) -> Vec<QMove> {
+    let q = e.fetch_QStruct();
+    let mut v: Vec<QMove> = q.candidate_nq_pairs().into_iter().map(|x| (Some(x),None)).collect();
+    let b = q.one_move();
+    if !b.0.is_none() && !v.contains(&b) {
+        v.push(b);
+    }
+    v
+}
+
+/// outcome of the state of `e`; see `MCTSConfig`
+pub fn state_value<D:AnsDomain>(cfg:&MCTSConfig,e:&mut rnb_env::RNBENV<D>) -> f32 {
+    let q = e.fetch_QStruct();
+    let c = q.c as f32;
+    let d = q.dead_nodes.len() as f32;
+    let mut n: HashSet<usize> = q.dead_nodes.clone();
+    n.extend(q.f2_nodes.iter().cloned());
+    let dc = n.into_iter().filter(|x| e.fetch_node(*x).db.obj.values().any(|o| *o == 1)).count() as f32;
+    cfg.fuel_weight * c + cfg.dead_weight * d + cfg.deceptive_weight * dc
+}
+
+/// # description
+/// searches the moves of Q in `e` by `cfg`.
+/// # return
+/// (move,visits,mean outcome) of each explored move of the root,
+/// by most visits
+pub fn search<D:AnsDomain>(cfg:&MCTSConfig,e:&mut rnb_env::RNBENV<D>) -> Vec<(QMove,usize,f32)> {
+    let s = std_rng::save_state();
+    std_rng::set_seed(cfg.seed);
+
+    let mut root = e.clone_sim();
+    let c0 = root.fetch_QStruct().c;
+    let budget = cfg.fuel_budget.unwrap_or(c0);
+    let v0 = state_value(cfg,&mut root);
+    let mut tree = vec![build_MCTSNode((None,None),candidate_moves(&mut root))];
+
+    for _ in 0..cfg.iterations {
+        let mut x = root.clone_sim();
+        let mut path = vec![0];
+        let mut k = 0;
+        let mut d = 0;
+        let within = |x:&mut rnb_env::RNBENV<D>| c0 - x.fetch_QStruct().c < budget;
+
+        // selection
+        while tree[k].untried.len() == 0 && tree[k].children.len() > 0 && d < cfg.depth && within(&mut x) {
+            let n = tree[k].visits as f32;
+            k = *tree[k].children.iter().max_by(|a,b| {
+                let u = |i:usize| tree[i].value / tree[i].visits as f32 +
+                    cfg.exploration * (n.ln() / tree[i].visits as f32).sqrt();
+                u(**a).partial_cmp(&u(**b)).unwrap()
+            }).unwrap();
+            x.execute_planned_Q_move(tree[k].mv.0,tree[k].mv.1,false);
+            path.push(k);
+            d += 1;
+        }
+
+        // expansion
+        if tree[k].untried.len() > 0 && d < cfg.depth && within(&mut x) {
+            let i = random_index(tree[k].untried.len());
+            let m = tree[k].untried.swap_remove(i);
+            x.execute_planned_Q_move(m.0,m.1,false);
+            let u = candidate_moves(&mut x);
+            tree.push(build_MCTSNode(m,u));
+            let k2 = tree.len() - 1;
+            tree[k].children.push(k2);
+            path.push(k2);
+            d += 1;
+        }
+
+        // rollout
+        while d < cfg.depth && within(&mut x) {
+            let u = candidate_moves(&mut x);
+            if u.len() == 0 {
+                break;
+            }
+            let m = u[random_index(u.len())];
+            if !x.execute_planned_Q_move(m.0,m.1,false) {
+                break;
+            }
+            d += 1;
+        }
+
+        let r = state_value(cfg,&mut x) - v0;
+        for i in path.into_iter() {
+            tree[i].visits += 1;
+            tree[i].value += r;
+        }
+    }
+
+    std_rng::restore_state(s);
+    let mut v: Vec<(QMove,usize,f32)> = tree[0].children.iter()
+        .map(|i| (tree[*i].mv,tree[*i].visits,tree[*i].value / tree[*i].visits as f32)).collect();
+    v.sort_by(|a,b| b.1.cmp(&a.1).then(b.2.partial_cmp(&a.2).unwrap()));
+    v
+}
+
+/// # description
+/// move of Q in `e` chosen by search; `QStruct::one_move` if no move
+/// is explored.
+pub fn plan<D:AnsDomain>(cfg:&MCTSConfig,e:&mut rnb_env::RNBENV<D>) -> QMove {
+    let v = search(cfg,e);
+    if v.len() == 0 {
+        return e.fetch_QStruct().one_move();
+    }
+    v[0].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__mcts_plan_seed() {
+        let mut e = rnb_env::sample_RNBENV1();
+        let cfg = build_MCTSConfig(60,3,11);
+        let c = e.fetch_QStruct().c;
+
+        let v1 = search(&cfg,&mut e);
+        let v2 = search(&cfg,&mut e);
+        assert_eq!(v1,v2);
+        assert_eq!(v1.iter().map(|x| x.1).sum::<usize>(),60);
+
+        // search does not change the environment
+        assert_eq!(e.fetch_QStruct().c,c);
+        assert_eq!(e.fetch_QStruct().rd.y.sum(),0);
+
+        // choice is pinned by the seed
+        let m = plan(&cfg,&mut e);
+        assert_eq!(m,v1[0].0);
+        assert_eq!(m,(Some((10,1)),None));
+    }
+
+    #[test]
+    fn test__mcts_planner_moves() {
+        let mut e = rnb_env::sample_RNBENV1();
+        let mut cfg = build_MCTSConfig(20,2,5);
+        cfg.fuel_budget = Some(30);
+        e.planner = Some(cfg);
+
+        for i in 0..3 {
+            e.new_round(i);
+            assert!(e.execute_Q_move(false));
+        }
+        let q = e.fetch_QStruct();
+        assert!(q.c < 2000);
+        assert_eq!(q.rd.y.sum(),3);
+    }
+}
@@ -0,0 +1,70 @@
+//! several interrogators (QStructs) on one network.
+//! Nodes' resistance and delegation trust are shared by all Qs; each Q has
+//! its own question set, QData, fuel and strategy.
+use crate::q_struct;
+use crate::fuel;
+use crate::ans_domain::AnsDomain;
+use crate::id_registry::NodeId;
+use std::collections::HashSet;
+use std::fmt;
+
+/// what a Q learns from the queries of other Qs
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum QSharing {
+    /// every response is logged into the QData of every Q
+    Cooperative,
+    /// a response is logged only into the QData of the Q that asked
+    Competitive
+}
+
+/// order of moves of Qs in a round
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum QTurns {
+    /// each Q moves in turn and sees the moves before it
+    Interleaved,
+    /// every Q chooses its move from the state at the start of the round
+    Simultaneous
+}
+
+/// outcome of one Q
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct QOutcome {
+    pub queries: usize,
+    pub f2_fixes: usize,
+    // nodes whose resistance ran out on a query of this Q
+    pub kills: usize,
+    // external ids of those nodes
+    pub killed: Vec<NodeId>,
+    pub fuel_spent: i32,
+    pub fuel_left: i32
+}
+
+impl fmt::Display for QOutcome {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let k:Vec<String> = self.killed.iter().map(|x| x.to_string()).collect();
+        write!(f, "queries {} F2 fixes {} kills {} [{}] fuel spent {} fuel left {}",
+            self.queries,self.f2_fixes,self.kills,k.join(","),self.fuel_spent,self.fuel_left)
+    }
+}
+
+/// # description
+/// interrogator of questions `qset` only; Q does not ask the other
+/// questions of `q`.
+pub fn build_interrogator<D:AnsDomain>(mut q:q_struct::QStruct<D>,qset:HashSet<usize>) -> q_struct::QStruct<D> {
+    for i in 0..q.qs.len() {
+        if !qset.contains(&i) {
+            q.retired_qs.insert(i);
+        }
+    }
+    q
+}
+
+/// # description
+/// outcome of `q` from its fuel log; `killed` is recorded by the environment.
+pub fn q_outcome<D:AnsDomain>(q:&q_struct::QStruct<D>,killed:Vec<NodeId>) -> QOutcome {
+    let queries = q.fuel.log.iter().filter(|x| x.accepted && x.action == fuel::FuelAction::Query).count();
+    let f2 = q.fuel.log.iter().filter(|x| x.accepted && x.action == fuel::FuelAction::F2).count();
+    let s = q.fuel.log.iter().filter(|x| x.action != fuel::FuelAction::Regen).map(|x| -x.delta).sum();
+    QOutcome{queries:queries,f2_fixes:f2,kills:killed.len(),killed:killed,fuel_spent:s,fuel_left:q.c}
+}
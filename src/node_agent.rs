@@ -0,0 +1,94 @@
+//! interface for external control of nodes.
+//! A node with a NodeController decides what to answer, whether to
+//! delegate and which neighbors to delegate to from its local observation;
+//! a decision of None falls back to the built-in behaviour of RNBNode.
+use crate::rndb;
+use std::collections::HashMap;
+
+/// what a node knows when it decides
+#[derive(Clone)]
+pub struct NodeObservation<A = i32> {
+    pub idn: usize,
+    pub round: usize,
+    pub db: rndb::RNDB<A>,
+    pub neighbors: Vec<usize>,
+    // neighbor -> satisfaction rate of node with neighbor for the question;
+    // 1 if never delegated
+    pub neighbor_sat: HashMap<usize,f32>,
+    pub resistance: f32
+}
+
+pub trait NodeController<A> {
+
+    /// answer to question `qi`
+    fn answer(&mut self,obs:&NodeObservation<A>,qi:usize) -> Option<A>;
+
+    /// if node delegates question `qi`; `dscore` is the distance of the
+    /// delegation answer to Q's answer, `rfeedback` the last resistance
+    /// loss of a direct answer.
+    fn delegate(&mut self,obs:&NodeObservation<A>,qi:usize,dscore:f32,rfeedback:f32) -> Option<bool>;
+
+    /// delegates of node for question `qi` among `candidates`
+    fn delegates(&mut self,obs:&NodeObservation<A>,qi:usize,candidates:&Vec<usize>) -> Option<Vec<usize>>;
+}
+
+/// # description
+/// local observation of node of db `db` for question `qi`
+pub fn build_NodeObservation<A:Clone>(idn:usize,round:usize,db:&rndb::RNDB<A>,neighbors:&Vec<usize>,
+    resistance:f32,qi:usize) -> NodeObservation<A> {
+    let ns = neighbors.iter().map(|n| {
+        let s = db.sat_other.get(n).and_then(|x| x.get(&qi)).cloned().unwrap_or(1.);
+        (*n,s)
+    }).collect();
+    NodeObservation{idn:idn,round:round,db:db.clone(),neighbors:neighbors.clone(),
+        neighbor_sat:ns,resistance:resistance}
+}
+
+/// scripted node: fixed answers, delegation choice and delegates
+#[derive(Clone,Debug,Default)]
+pub struct ScriptedNode<A = i32> {
+    // question -> answer
+    pub answers: HashMap<usize,A>,
+    pub delegate: Option<bool>,
+    // delegates are the candidates in `only`
+    pub only: Option<Vec<usize>>
+}
+
+impl<A:Clone> NodeController<A> for ScriptedNode<A> {
+
+    fn answer(&mut self,_obs:&NodeObservation<A>,qi:usize) -> Option<A> {
+        self.answers.get(&qi).cloned()
+    }
+
+    fn delegate(&mut self,_obs:&NodeObservation<A>,_qi:usize,_dscore:f32,_rfeedback:f32) -> Option<bool> {
+        self.delegate
+    }
+
+    fn delegates(&mut self,_obs:&NodeObservation<A>,_qi:usize,candidates:&Vec<usize>) -> Option<Vec<usize>> {
+        self.only.as_ref().map(|v| candidates.iter().filter(|x| v.contains(x)).cloned().collect())
+    }
+}
+
+/// node that trusts the neighbors of satisfaction rate at least
+/// `threshold` and delegates when its last resistance loss exceeds
+/// `max_loss`.
+#[derive(Clone,Debug)]
+pub struct GreedyNode {
+    pub threshold: f32,
+    pub max_loss: f32
+}
+
+impl<A> NodeController<A> for GreedyNode {
+
+    fn answer(&mut self,_obs:&NodeObservation<A>,_qi:usize) -> Option<A> {
+        None
+    }
+
+    fn delegate(&mut self,_obs:&NodeObservation<A>,_qi:usize,dscore:f32,rfeedback:f32) -> Option<bool> {
+        Some(rfeedback > self.max_loss && dscore < rfeedback)
+    }
+
+    fn delegates(&mut self,obs:&NodeObservation<A>,_qi:usize,candidates:&Vec<usize>) -> Option<Vec<usize>> {
+        Some(candidates.iter().filter(|x| obs.neighbor_sat.get(x).cloned().unwrap_or(1.) >= self.threshold).cloned().collect())
+    }
+}
@@ -0,0 +1,163 @@
+//! lifecycle state of a node.
+//! The state of a node is made of its resistance, its death for Q and
+//! its F1 and F2 fixes; `NodeState` names each combination that can
+//! occur and `NodeState::transition` the changes between them. A node
+//! that is dead is never queried, a delegate or logged.
+
+/// event that changes the state of a node
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum NodeEvent {
+    /// resistance drops to 0 or below
+    Exhaust,
+    /// resistance rises above 0 again; an F1 pattern is released
+    Regenerate,
+    /// Q marks node dead; an F2 restriction is released
+    Kill,
+    /// dead node rejoins the network with resistance
+    Revive,
+    FixF1,
+    ReleaseF1,
+    FixF2,
+    ReleaseF2
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum NodeState {
+    Active,
+    /// no resistance left; not dead for Q yet
+    Exhausted,
+    /// exhausted node that answers by its F1 answer pattern
+    F1Fixed,
+    /// barred from delegation by F2
+    F2Restricted,
+    ExhaustedF2Restricted,
+    F1FixedF2Restricted,
+    Dead,
+    /// dead node with an F1 answer pattern
+    DeadF1Fixed
+}
+
+/// state of a node that is (exhausted,dead,F1 fixed,F2 restricted);
+/// dead implies exhausted and not F2 restricted, F1 implies exhausted.
+pub fn build_NodeState(exhausted:bool,dead:bool,f1:bool,f2:bool) -> NodeState {
+    match (dead,f1,f2 && !dead,exhausted || dead || f1) {
+        (true,false,_,_) => NodeState::Dead,
+        (true,true,_,_) => NodeState::DeadF1Fixed,
+        (false,true,false,_) => NodeState::F1Fixed,
+        (false,true,true,_) => NodeState::F1FixedF2Restricted,
+        (false,false,false,true) => NodeState::Exhausted,
+        (false,false,true,true) => NodeState::ExhaustedF2Restricted,
+        (false,false,true,false) => NodeState::F2Restricted,
+        (false,false,false,false) => NodeState::Active
+    }
+}
+
+impl NodeState {
+
+    /// (exhausted,dead,F1 fixed,F2 restricted)
+    pub fn parts(&self) -> (bool,bool,bool,bool) {
+        match self {
+            NodeState::Active => (false,false,false,false),
+            NodeState::Exhausted => (true,false,false,false),
+            NodeState::F1Fixed => (true,false,true,false),
+            NodeState::F2Restricted => (false,false,false,true),
+            NodeState::ExhaustedF2Restricted => (true,false,false,true),
+            NodeState::F1FixedF2Restricted => (true,false,true,true),
+            NodeState::Dead => (true,true,false,false),
+            NodeState::DeadF1Fixed => (true,true,true,false)
+        }
+    }
+
+    /// # description
+    /// state after event `e`.
+    /// # return
+    /// None if `e` can not occur in this state
+    pub fn transition(&self,e:NodeEvent) -> Option<NodeState> {
+        let (ex,d,f1,f2) = self.parts();
+        match e {
+            NodeEvent::Exhaust if !ex => Some(build_NodeState(true,d,f1,f2)),
+            NodeEvent::Regenerate if ex && !d => Some(build_NodeState(false,d,false,f2)),
+            NodeEvent::Kill if !d => Some(build_NodeState(true,true,f1,false)),
+            NodeEvent::Revive if d => Some(build_NodeState(false,false,false,false)),
+            NodeEvent::FixF1 if ex && !f1 => Some(build_NodeState(ex,d,true,f2)),
+            NodeEvent::ReleaseF1 if f1 => Some(build_NodeState(ex,d,false,f2)),
+            NodeEvent::FixF2 if !d && !f2 => Some(build_NodeState(ex,d,f1,true)),
+            NodeEvent::ReleaseF2 if f2 => Some(build_NodeState(ex,d,f1,false)),
+            _ => None
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.parts().1
+    }
+
+    pub fn has_resistance(&self) -> bool {
+        !self.parts().0
+    }
+
+    /// Q may query the node
+    pub fn can_be_queried(&self) -> bool {
+        self.is_alive()
+    }
+
+    /// node may answer as a delegate of another node; an F1 fixed node
+    /// answers by its pattern
+    pub fn can_delegate(&self) -> bool {
+        matches!(self,NodeState::Active | NodeState::Exhausted | NodeState::F1Fixed)
+    }
+
+    /// answers of the node may be logged by Q
+    pub fn can_be_logged(&self) -> bool {
+        self.is_alive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATES: [NodeState;8] = [NodeState::Active,NodeState::Exhausted,NodeState::F1Fixed,
+        NodeState::F2Restricted,NodeState::ExhaustedF2Restricted,NodeState::F1FixedF2Restricted,
+        NodeState::Dead,NodeState::DeadF1Fixed];
+    const EVENTS: [NodeEvent;8] = [NodeEvent::Exhaust,NodeEvent::Regenerate,NodeEvent::Kill,
+        NodeEvent::Revive,NodeEvent::FixF1,NodeEvent::ReleaseF1,NodeEvent::FixF2,NodeEvent::ReleaseF2];
+
+    #[test]
+    fn test__NodeState_parts() {
+        for s in STATES.iter() {
+            let (ex,d,f1,f2) = s.parts();
+            assert_eq!(build_NodeState(ex,d,f1,f2),*s);
+        }
+        // F2 is released on death
+        assert_eq!(build_NodeState(true,true,false,true),NodeState::Dead);
+        assert_eq!(build_NodeState(false,false,true,false),NodeState::F1Fixed);
+    }
+
+    #[test]
+    fn test__NodeState_transition() {
+        let s = NodeState::Active;
+        let s = s.transition(NodeEvent::FixF2).unwrap();
+        assert_eq!(s,NodeState::F2Restricted);
+        assert!(s.transition(NodeEvent::FixF1).is_none());
+        let s = s.transition(NodeEvent::Exhaust).unwrap();
+        let s = s.transition(NodeEvent::FixF1).unwrap();
+        assert_eq!(s,NodeState::F1FixedF2Restricted);
+        let s = s.transition(NodeEvent::Kill).unwrap();
+        assert_eq!(s,NodeState::DeadF1Fixed);
+        assert!(s.transition(NodeEvent::FixF2).is_none());
+        assert_eq!(s.transition(NodeEvent::Revive),Some(NodeState::Active));
+
+        // every transition keeps a dead node out of queries and delegation
+        for s in STATES.iter() {
+            for e in EVENTS.iter() {
+                let x = s.transition(*e);
+                if x.is_none() {
+                    continue;
+                }
+                let x = x.unwrap();
+                assert_eq!(x.is_alive(),*e != NodeEvent::Kill && (s.is_alive() || *e == NodeEvent::Revive));
+                assert!(x.is_alive() || !(x.can_be_queried() || x.can_delegate() || x.can_be_logged()));
+            }
+        }
+    }
+}
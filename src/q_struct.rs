@@ -3,8 +3,14 @@ use crate::seq_encoder;
 use crate::ans; 
 use crate::std_rng;
 use crate::qsbf;
+use crate::f1detect;
+use crate::truth;
+use crate::fuel;
+use crate::info_gain;
+use crate::ans_domain;
+use crate::ans_domain::{AnsDomain,AnsValue,MixedDomain};
 use ndarray::{arr1,arr2,Array,Array1,Array2,s,ScalarOperand};
-use std::collections::HashSet;
+use std::collections::{HashMap,HashSet};
 use std::ops::Add;
 use num_traits::identities::Zero;
 
@@ -35,40 +41,182 @@ where T:Clone + Default + Add<Output=T> + Zero + ScalarOperand {
 
 /// question struct
 #[derive(Clone)]
-pub struct Q {
+pub struct Q<D:AnsDomain = (i32,i32)> {
     // answer to question 
-    pub qa: Option<i32>,
-    pub ans_range: (i32,i32)
+    pub qa: Option<D::Ans>,
+    // answer domain of question
+    pub ans_range: D
 }
 
-pub struct QStruct {
-    pub qs: Vec<Q>,
-    pub rd: rdata::QData,
+#[derive(Clone)]
+pub struct QStruct<D:AnsDomain = (i32,i32)> {
+    pub qs: Vec<Q<D>>,
+    pub rd: rdata::QData<D::Ans>,
 
     /// fix type 1 and type 2 filters
+    /// nodes of the F2 fixes of Q in the fix registry of RNBENV; kept by
+    /// `F2Fix` so that the selection functions of Q need no registry
     pub f2_nodes: HashSet<usize>,
 
     // fuel level
     pub c: i32,
+    // costs of actions and log of fuel transactions
+    pub fuel: fuel::FuelModel,
 
     // register dead nodes
-    pub dead_nodes:HashSet<usize>
+    pub dead_nodes:HashSet<usize>,
+
+    /// (node,question) -> detected answer pattern
+    pub f1_detected: HashMap<(usize,usize),f1detect::F1Detection<D::Ans>>,
+    /// number of repeats of a period needed for detection
+    pub f1_reps: usize,
+
+    /// estimator of answers to questions without known answer
+    pub truth_est: truth::TruthEstimator,
+
+    /// mode of charging contradiction of a delegated response
+    pub attribution: rdata::Attribution,
+
+    /// questions no longer asked
+    pub retired_qs: HashSet<usize>,
+
+    /// policy of choosing the (node,question) pair of a query
+    pub selector: info_gain::NQSelector,
+
+    /// fix type 3 filter: (node,delegate) edges severed
+    pub severed: HashSet<(usize,usize)>,
+    /// minimum score of an edge for Q to sever it on a move;
+    /// None for no F3 fixes
+    pub f3_min_score: Option<f32>
 }
 
-pub fn build_QStruct(qs:Vec<Q>,r:usize,c:i32) -> QStruct {
+pub fn build_QStruct<D:AnsDomain>(qs:Vec<Q<D>>,r:usize,c:i32) -> QStruct<D> {
     let rd = rdata::build_QData(r,qs.len());
-    QStruct{qs:qs,rd:rd,f2_nodes:HashSet::new(),c:c,dead_nodes:HashSet::new()}
+    QStruct{qs:qs,rd:rd,f2_nodes:HashSet::new(),c:c,fuel:fuel::build_FuelModel(),dead_nodes:HashSet::new(),
+        f1_detected:HashMap::new(),f1_reps:3,truth_est:truth::TruthEstimator::YWeightedMean,
+        attribution:rdata::Attribution::Equal,retired_qs:HashSet::new(),
+        selector:info_gain::NQSelector::Priority,severed:HashSet::new(),f3_min_score:None}
 }
 
-impl QStruct {
+impl<D:AnsDomain> QStruct<D> {
+
+    pub fn response_to_nodeset(&mut self,srcidn: usize, ns:HashSet<usize>,qi:usize,nodeset_ans:D::Ans) {
+        // log the response into data, contradiction is against
+        // the answer of the truth estimator if unknown
+        let wanted = self.ans_to_q(qi);
+        self.rd.log_node_response(srcidn,ns,qi,self.qs[qi].ans_range.clone(),Some(wanted),nodeset_ans);
+        self.detect_F1(srcidn,qi);
+    }
+
+    /// # description
+    /// logs the response of node `srcidn` that carries the answer of each
+    /// node of its delegation; contradiction is charged by `attribution`.
+    pub fn response_to_answers(&mut self,srcidn: usize,answers:HashMap<usize,D::Ans>,qi:usize,nodeset_ans:D::Ans) -> HashMap<usize,f32> {
+        let wanted = self.ans_to_q(qi);
+        let x = self.rd.log_node_answers(srcidn,answers,qi,self.qs[qi].ans_range.clone(),Some(wanted),
+            nodeset_ans,&self.attribution.clone());
+        self.detect_F1(srcidn,qi);
+        x
+    }
+
+    /// # description
+    /// `response_to_answers` for a response that travelled the delegation
+    /// edges `edges`; logs the provenance of each delegate's answer.
+    pub fn response_to_delegation(&mut self,srcidn: usize,answers:HashMap<usize,D::Ans>,edges:&Vec<(usize,usize)>,qi:usize,nodeset_ans:D::Ans) {
+        let x = self.response_to_answers(srcidn,answers,qi,nodeset_ans);
+        self.rd.log_provenance(edges,&x);
+    }
+
+    /// # description
+    /// adds a data row for a new node
+    /// # return
+    /// idn of new node
+    pub fn add_node_row(&mut self) -> usize {
+        self.rd.add_row()
+    }
+
+    /// # description
+    /// removes the data of node `ni`; idns greater than `ni` move
+    /// down by one.
+    pub fn remove_node_row(&mut self,ni:usize) {
+        self.rd.remove_row(ni);
+        let f = |i:usize| if i == ni {None} else if i > ni {Some(i - 1)} else {Some(i)};
+        self.f2_nodes = self.f2_nodes.iter().filter_map(|x| f(*x)).collect();
+        self.dead_nodes = self.dead_nodes.iter().filter_map(|x| f(*x)).collect();
+        self.f1_detected = self.f1_detected.drain().filter_map(|(k,v)| f(k.0).map(|k2| ((k2,k.1),v))).collect();
+        self.severed = self.severed.iter().filter_map(|x| Some((f(x.0)?,f(x.1)?))).collect();
+    }
+
+    /// # description
+    /// adds question `q`
+    /// # return
+    /// index of new question
+    pub fn add_question(&mut self,q:Q<D>) -> usize {
+        self.qs.push(q);
+        self.rd.add_col()
+    }
+
+    /// # description
+    /// retires question `qi`; Q no longer asks it and its data is
+    /// cleared. The index of `qi` is not reused.
+    pub fn retire_question(&mut self,qi:usize) {
+        self.retired_qs.insert(qi);
+        self.rd.clear_col(qi);
+        self.f1_detected.retain(|k,_| k.1 != qi);
+    }
+
+    /// # description
+    /// checks the answer log of node `ni` for question `qi` for a periodic
+    /// or deterministic pattern; registers the detection in `f1_detected`.
+    /// A repeated answer (period 1) is a pattern only if it contradicts
+    /// Q's answer, so that a consistent honest node is not flagged.
+    pub fn detect_F1(&mut self,ni:usize,qi:usize) -> Option<f1detect::F1Detection<D::Ans>> {
+        if !self.rd.al.contains_key(&(ni,qi)) {
+            return None;
+        }
+
+        let mut x = f1detect::detect_pattern(&self.rd.al[&(ni,qi)],self.f1_reps,&self.qs[qi].ans_range);
+        if !x.is_none() && x.as_ref().unwrap().period == Some(1) {
+            let a = self.ans_to_q(qi);
+            if self.qs[qi].ans_range.contradiction(&a,&x.as_ref().unwrap().next) == 0. {
+                x = None;
+            }
+        }
+        if x.is_none() {
+            self.f1_detected.remove(&(ni,qi));
+        } else {
+            self.f1_detected.insert((ni,qi),x.clone().unwrap());
+        }
+        x
+    }
+
+    /// # description
+    /// predicted next answer of node `ni` to question `qi` if node has a
+    /// detected answer pattern
+    pub fn predict_ans(&mut self,ni:usize,qi:usize) -> Option<D::Ans> {
+        if !self.f1_detected.contains_key(&(ni,qi)) {
+            return None;
+        }
+        Some(self.f1_detected[&(ni,qi)].next.clone())
+    }
+
+    /// # description
+    /// nodes with a detected answer pattern for any question
+    pub fn f1_flagged_nodes(&mut self) -> HashSet<usize> {
+        self.f1_detected.keys().map(|x| x.0).collect()
+    }
 
-    pub fn response_to_nodeset(&mut self,srcidn: usize, ns:HashSet<usize>,qi:usize,nodeset_ans:i32) {
-        // log the response into data
-        self.rd.log_node_response(srcidn,ns,qi,self.qs[qi].ans_range.clone(),self.qs[qi].qa.clone(),nodeset_ans);
+    /// # description
+    /// if Q does not query node `ni` on question `qi`: node is dead,
+    /// question is retired or node has a detected answer pattern for
+    /// question. Q does not spend fuel on a pattern it can predict, but
+    /// still asks the node its other questions.
+    pub fn is_unqueried_nq_pair(&self,ni:usize,qi:usize) -> bool {
+        self.dead_nodes.contains(&ni) || self.retired_qs.contains(&qi) || self.f1_detected.contains_key(&(ni,qi))
     }
 
     /// method used in the case of known and unknown     
-    pub fn ans_to_q(&mut self,qi:usize) -> i32 {
+    pub fn ans_to_q(&mut self,qi:usize) -> D::Ans {
 
         // case: QStruct has a known answer
         if !self.qs[qi].qa.is_none() {
@@ -76,10 +224,25 @@ impl QStruct {
         }
 
         // case: QStruct does not have known answer
-        self.rd.average_ans_to_question(qi,self.qs[qi].ans_range.clone())
+        self.estimate_ans(qi).ans
+    }
+
+    /// # description
+    /// answer to question `qi` by the truth estimator `truth_est`
+    pub fn estimate_ans(&mut self,qi:usize) -> truth::TruthEstimate<D::Ans> {
+        truth::estimate_truth(&self.truth_est,&self.qs,&self.rd,qi)
     }
 
-    pub fn ans_vec(&mut self) -> Array1<i32> {
+    /// # description
+    /// confidence of `ans_to_q`; 1 for a known answer.
+    pub fn ans_confidence(&mut self,qi:usize) -> f32 {
+        if !self.qs[qi].qa.is_none() {
+            return 1.;
+        }
+        self.estimate_ans(qi).confidence
+    }
+
+    pub fn ans_vec(&mut self) -> Array1<D::Ans> {
         let l = self.qs.len();
         (0..l).into_iter().map(|x| self.ans_to_q(x)).collect()
     }
@@ -90,29 +253,86 @@ impl QStruct {
     /// [0] target node and question pair
     /// [1] target node for F1 and QStruct fuel change
     pub fn one_move(&mut self) -> (Option<(usize,usize)>,Option<(usize,i32)>) {
-        let mut nq = self.priority_nq_pair();
+        let mut nq = self.select_nq_pair();
+        let n = self.F2_move();
+        (nq,n) 
+    }
+
+    /// # description
+    /// F2 fix of `one_move`; see `qsbf::qbot_function_1_domain`.
+    /// # return
+    /// target node for F2 and QStruct fuel change
+    pub fn F2_move(&mut self) -> Option<(usize,i32)> {
         let av = self.ans_vec();
 
         // fetch filtered delegate matrix
         let w = self.filtered_delegate_matrix();
-        let n = qsbf::qbot_function_1(self.rd.z.clone(),self.rd.w.clone(),
-            self.ans_vec(),self.f2_nodes.clone());
-        (nq,n) 
+        let doms:Vec<D> = self.qs.iter().map(|q| q.ans_range.clone()).collect();
+        qsbf::qbot_function_1_domain(self.rd.z.clone(),w,
+            av,&doms,self.f2_nodes.clone())
+    }
+
+    /// # description
+    /// score of F2 fix on node `ni`; see `qsbf::qbot_base_function_domain`.
+    pub fn F2_score(&mut self,ni:usize) -> i32 {
+        let av = self.ans_vec();
+        let doms:Vec<D> = self.qs.iter().map(|q| q.ans_range.clone()).collect();
+        let z:Array1<D::Ans> = self.rd.z.slice(s![ni,..]).to_owned();
+        let w:Array1<i32> = self.rd.w.slice(s![ni,..]).iter().map(|x| *x as i32).collect();
+        qsbf::qbot_base_function_domain(z,w,av,&doms)
+    }
+
+    /// # description
+    /// batch of at most `k` nodes to fix by F2 with total F2 cost at
+    /// most `budget`; see `qsbf::qbot_function_3_domain`.
+    pub fn F2_batch(&mut self,k:usize,budget:i32) -> Vec<(usize,i32)> {
+        let av = self.ans_vec();
+        let doms:Vec<D> = self.qs.iter().map(|q| q.ans_range.clone()).collect();
+        let f = self.fuel.clone();
+        qsbf::qbot_function_3_domain(self.rd.z.clone(),self.filtered_delegate_matrix(),av,&doms,self.f2_nodes.clone(),
+            k,budget,&|x| f.cost(fuel::FuelAction::F2,x))
+    }
+
+    /// # description
+    /// nodes that are not dead or fixed by F2 by expected share of future
+    /// delegations; see `qsbf::qbot_function_4`.
+    pub fn delegation_ranking(&mut self,prior:f32) -> Vec<(usize,f32)> {
+        let mut ex = self.dead_nodes.clone();
+        ex.extend(self.f2_nodes.iter().cloned());
+        qsbf::qbot_function_4(self.rd.w.clone(),ex,prior)
+    }
+
+    /// # description
+    /// edge to sever by F3 if its score is at least `f3_min_score`;
+    /// see `qsbf::qbot_function_5`.
+    pub fn F3_target(&mut self) -> Option<((usize,usize),f32)> {
+        if self.f3_min_score.is_none() {
+            return None;
+        }
+        let x = qsbf::qbot_function_5(&self.rd.dl,self.dead_nodes.clone(),&self.severed);
+        x.filter(|y| y.1 >= self.f3_min_score.unwrap())
     }
 
-    /// marks any delegate matrix row as 0 if it is present in f2_nodes
+    /// delegate matrix with the rows of nodes in f2_nodes or dead_nodes
+    /// marked as 0; these nodes can not be F2 targets.
     pub fn filtered_delegate_matrix(&mut self) -> Array2<usize> {
-        let (r,c) = self.rd.w.dim();
-        let mut dm:Array2<usize> = Array2::zeros((r,c));        
-        let zs: Array1<usize> = Array1::zeros(c); 
-        for i in 0..r {
-            if self.f2_nodes.contains(&i) {
-                continue; 
-            }
-            let mut b = dm.slice_mut(s![i,..]); 
-            b.assign(&(zs.clone()));
+        let mut ex = self.dead_nodes.clone();
+        ex.extend(self.f2_nodes.iter().cloned());
+        dead_node_filter(self.rd.w.clone(),ex,0)
+    }
+
+    /// if an F2 fix on node `ni` has an effect: node is alive, not fixed
+    /// by F2 yet and was a delegate
+    pub fn is_F2_target(&mut self,ni:usize) -> bool {
+        ni < self.rd.w.dim().0 && self.filtered_delegate_matrix().slice(s![ni,..]).sum() > 0
+    }
+
+    /// (node,question) pair of the next query by `selector`
+    pub fn select_nq_pair(&mut self) -> Option<(usize,usize)> {
+        match self.selector.clone() {
+            info_gain::NQSelector::Priority => self.priority_nq_pair(),
+            info_gain::NQSelector::InfoGain(m) => info_gain::info_gain_nq_pair(&m,self)
         }
-        dm
     }
 
     /// # description
@@ -136,24 +356,43 @@ impl QStruct {
     /// of (QData.x * QData.y)|(QData.x * QData.w)  
     pub fn max_contra_nq_pair(&mut self) -> Option<(usize,usize)> {
         let (r,c) = self.rd.x.dim();
+
+        let ex = self.dead_nodes.clone();
+
         // case: all nodes are dead
-        if self.dead_nodes.len() == r {
+        if ex.len() == r {
             return None;
         }
 
         // calculate x * y
             // convert y to f32
-        let y = dead_node_filter(self.rd.y.clone(),self.dead_nodes.clone(),0); 
+        let y = dead_node_filter(self.rd.y.clone(),ex.clone(),0); 
         let y2:Vec<f32> = y.clone().into_iter().map(|x| x as f32).collect();
         let y_:Array2<f32> = Array::from_shape_vec((r,c),y2).unwrap();
-        let xy = self.rd.x.clone() * y_;
+        let mut xy = self.rd.x.clone() * y_;
 
         // calculate x * w
             // convert w to f32
-        let w = dead_node_filter(self.rd.w.clone(),self.dead_nodes.clone(),0); 
+        let ex2 = ex.clone();
+        let w = dead_node_filter(self.rd.w.clone(),ex,0); 
         let w2:Vec<f32> = w.clone().into_iter().map(|x| x as f32).collect();
         let w_:Array2<f32> = Array::from_shape_vec((r,c),w2).unwrap();
-        let xw = self.rd.x.clone() * w_; 
+        let mut xw = self.rd.x.clone() * w_; 
+
+        // dead nodes, retired questions and pairs of detected answer
+        // patterns are below any contradiction
+        for i in ex2.iter() {
+            xy.slice_mut(s![*i,..]).fill(-1.);
+            xw.slice_mut(s![*i,..]).fill(-1.);
+        }
+        for j in self.retired_qs.iter() {
+            xy.slice_mut(s![..,*j]).fill(-1.);
+            xw.slice_mut(s![..,*j]).fill(-1.);
+        }
+        for k in self.f1_detected.keys() {
+            xy[[k.0,k.1]] = -1.;
+            xw[[k.0,k.1]] = -1.;
+        }
 
         // determine max of x * y
         let (i,m1) = xy.into_iter().enumerate().fold((0,f32::MIN),
@@ -163,6 +402,11 @@ impl QStruct {
         let (i2,m2) = xw.into_iter().enumerate().fold((0,f32::MIN),
             |x,x2| if x.1 < x2.1 {x2} else {x}); 
 
+        // case: no pair left to query
+        if m1 < 0. && m2 < 0. {
+            return None;
+        }
+
         // output index of max
         if m1 > m2 {
             return Some(arr1_index_to_arr2_index(i,(r,c)));
@@ -171,6 +415,19 @@ impl QStruct {
     }
     
 
+    /// # description
+    /// (node,question) pairs Q may query; see `is_unqueried_nq_pair`.
+    pub fn candidate_nq_pairs(&mut self) -> Vec<(usize,usize)> {
+        let (r,c) = self.rd.x.dim();
+        let mut v: Vec<(usize,usize)> = Vec::new();
+        for i in 0..r {
+            for j in (0..c).filter(|j| !self.is_unqueried_nq_pair(i,*j)) {
+                v.push((i,j));
+            }
+        }
+        v
+    }
+
     /// choose random (node,question) pair in QData.y that is 0  
     pub fn random_unanswered_nq_pair(&mut self) -> Option<(usize,usize)> {
         let mut qi: Vec<usize> = Vec::new();
@@ -178,8 +435,11 @@ impl QStruct {
 
         // collect all questions with  >= 1 nodes that did not answer
         for i in 0..c {
+            if self.retired_qs.contains(&i) {
+                continue;
+            }
             let r2:Array1<usize> = self.rd.y.slice(s![..,i]).to_owned();
-            let r3:Array1<usize> = r2.into_iter().enumerate().filter(|x| x.1 == 0).map(|x| x.0).collect(); 
+            let r3:Array1<usize> = r2.into_iter().enumerate().filter(|x| x.1 == 0 && !self.is_unqueried_nq_pair(x.0,i)).map(|x| x.0).collect(); 
             if r3.len() > 0 {
                 qi.push(i);
             }
@@ -193,7 +453,7 @@ impl QStruct {
         
         // collect nodes that did not answer question
         let mut ni2:Array1<usize> = self.rd.y.slice(s![..,qi[qic]]).to_owned().clone();
-        let ni:Array1<usize> = ni2.clone().into_iter().enumerate().filter(|x| x.1 == 0).map(|x| x.0).collect(); 
+        let ni:Array1<usize> = ni2.clone().into_iter().enumerate().filter(|x| x.1 == 0 && !self.is_unqueried_nq_pair(x.0,qi[qic])).map(|x| x.0).collect(); 
         let nic = std_rng::random_i32_in_range((0,ni.len() as i32 - 1)) as usize;
         Some((ni[nic],qi[qic]))
     } 
@@ -209,4 +469,100 @@ pub fn sample_QStruct1() -> QStruct {
     let q4 = Q{qa:Some(6),ans_range:(0,10)};
 
     build_QStruct(vec![q0,q1,q2,q3,q4],11,2000)
-}
\ No newline at end of file
+}
+
+/// questions of mixed answer domains: an integer, a probability, 
+/// a categorical choice and a 2-d vector. 
+pub fn sample_QStruct_mixed1() -> QStruct<ans_domain::MixedDomain> {
+    let q0 = Q{qa:Some(AnsValue::Int(6)),ans_range:MixedDomain::Int((0,10))};
+    let q1 = Q{qa:None,ans_range:MixedDomain::Real(ans_domain::RealRange(0.,1.))};
+    let q2 = Q{qa:Some(AnsValue::Cat(0)),ans_range:MixedDomain::Cat(
+        ans_domain::build_Categorical(vec!["red","green","blue"]))};
+    let q3 = Q{qa:Some(AnsValue::Vector(vec![1.,1.])),ans_range:MixedDomain::Vector(
+        ans_domain::build_VecRange(vec![0.,0.],vec![4.,4.]))};
+
+    build_QStruct(vec![q0,q1,q2,q3],4,500)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__QStruct_filtered_delegate_matrix() {
+        let mut q = sample_QStruct1();
+        for i in vec![2,3,5,7] {
+            q.rd.w[[i,0]] = 3;
+            q.rd.w[[i,2]] = 1;
+            q.rd.z[[i,0]] = 100;
+        }
+        q.f2_nodes.insert(3);
+        q.dead_nodes.insert(5);
+
+        let w = q.filtered_delegate_matrix();
+        assert_eq!(w.sum(),8);
+        assert_eq!(w.slice(s![3,..]).sum(),0);
+        assert_eq!(w.slice(s![5,..]).sum(),0);
+        assert!(q.is_F2_target(2) && q.is_F2_target(7));
+        assert!(!q.is_F2_target(3) && !q.is_F2_target(5) && !q.is_F2_target(0));
+
+        // F2 target is never a fixed or dead node
+        for _ in 0..2 {
+            let (_,n) = q.one_move();
+            let x = n.unwrap().0;
+            assert!(x == 2 || x == 7);
+            q.f2_nodes.insert(x);
+        }
+
+        // no target is left
+        let (_,n) = q.one_move();
+        assert!(n.is_none());
+        assert!(q.F2_batch(3,1000).is_empty());
+    }
+
+    #[test]
+    fn test__QStruct_detect_F1() {
+        let mut q = sample_QStruct1();
+
+        // node 3 answers question 0 with a pattern of period 2
+        let v = vec![90,75,60,75,60,75,60];
+        for (i,x) in v.iter().enumerate() {
+            q.response_to_nodeset(3,HashSet::from_iter(vec![3]),0,*x);
+            if i < 5 {
+                assert!(q.predict_ans(3,0).is_none());
+            }
+        }
+        assert_eq!(q.predict_ans(3,0),Some(75));
+        assert_eq!(q.f1_flagged_nodes(),HashSet::from_iter(vec![3]));
+
+        // flagged pair is never chosen for a query; node is still asked
+        // its other questions
+        q.rd.x[[3,0]] = 1.;
+        assert!(q.max_contra_nq_pair() != Some((3,0)));
+        let v = q.candidate_nq_pairs();
+        assert!(!v.contains(&(3,0)));
+        assert!(v.contains(&(3,1)));
+        for _ in 0..50 {
+            let x = q.random_unanswered_nq_pair();
+            assert!(x.unwrap() != (3,0));
+        }
+
+        // delegated answers are not logged as node answers
+        q.response_to_nodeset(4,HashSet::from_iter(vec![4,5]),0,50);
+        assert!(!q.rd.al.contains_key(&(4,0)));
+
+        // pattern breaks
+        q.response_to_nodeset(3,HashSet::from_iter(vec![3]),0,1);
+        assert!(q.predict_ans(3,0).is_none());
+        assert_eq!(q.f1_flagged_nodes().len(),0);
+
+        // node 5 repeats the answer of Q; node 6 repeats a lie
+        for _ in 0..5 {
+            q.response_to_nodeset(5,HashSet::from_iter(vec![5]),0,50);
+            q.response_to_nodeset(6,HashSet::from_iter(vec![6]),0,90);
+        }
+        assert!(q.predict_ans(5,0).is_none());
+        assert_eq!(q.predict_ans(6,0),Some(90));
+        assert_eq!(q.f1_flagged_nodes(),HashSet::from_iter(vec![6]));
+    }
+}
@@ -1,6 +1,10 @@
 //! QStruct bot functions
+use crate::ans_domain::AnsDomain;
 use ndarray::{arr1,arr2,Array,Array1,Array2,s};
-use std::collections::HashSet;
+use std::collections::{HashMap,HashSet};
+
+/// greatest budget of the knapsack of `qbot_function_3_domain`
+pub const MAX_KNAPSACK_BUDGET: usize = 1000;
 
 /// # description
 /// function that determines one node to fix by fix F2
@@ -18,16 +22,25 @@ use std::collections::HashSet;
 /// # return
 /// (node identifier,score) 
 pub fn qbot_function_1(z:Array2<i32>,w:Array2<usize>,wanted_answers:Array1<i32>,fixed_nodes:HashSet<usize>) -> Option<(usize,i32)> {    
+    let doms = vec![(i32::MIN,i32::MAX);z.dim().1];
+    qbot_function_1_domain(z,w,wanted_answers,&doms,fixed_nodes)
+}
+
+/// # description
+/// qbot_function_1 on answers of any domain; `doms` is the answer
+/// domain of each question.
+pub fn qbot_function_1_domain<D:AnsDomain>(z:Array2<D::Ans>,w:Array2<usize>,wanted_answers:Array1<D::Ans>,
+    doms:&Vec<D>,fixed_nodes:HashSet<usize>) -> Option<(usize,i32)> {    
     let mut q:Vec<usize> = delegate_nodes(w.clone());
     q = q.into_iter().filter(|x| !fixed_nodes.contains(&x)).collect();
     if q.len() == 0 {return None;}
 
     let mut scores:Vec<i32> = Vec::new();
     for i in q.clone().into_iter() {
-        let mut z1:Array1<i32> = z.slice(s![i,..]).to_owned();
+        let mut z1:Array1<D::Ans> = z.slice(s![i,..]).to_owned();
         let mut w1:Array1<i32> = w.slice(s![i,..]).to_owned().into_iter().map(|x| x as i32).collect();
         //println!("node {} score {}",i,s1);
-        scores.push(qbot_base_function(z1,w1,wanted_answers.clone())); 
+        scores.push(qbot_base_function_domain(z1,w1,wanted_answers.clone(),doms)); 
     }
 
     let n = scores.into_iter().enumerate().fold((0,i32::MAX),|x1,x2| if x1.1 <= x2.1 {x1} else {x2});
@@ -42,6 +55,159 @@ pub fn qbot_base_function(z:Array1<i32>,w:Array1<i32>,wanted_answers:Array1<i32>
     (w * (z - wanted_answers.clone())).into_iter().map(|x| x.abs()).sum()
 }
 
+/// # description
+/// qbot_base_function on answers of any domain; the difference of answers
+/// is the distance of the question's domain.
+pub fn qbot_base_function_domain<D:AnsDomain>(z:Array1<D::Ans>,w:Array1<i32>,wanted_answers:Array1<D::Ans>,doms:&Vec<D>) -> i32 {
+    (0..z.len()).into_iter().map(|i| w[i] as f32 * doms[i].distance(&z[i],&wanted_answers[i])).sum::<f32>().round() as i32
+}
+
+/// # description
+/// function that determines the exhausted node to fix first by fix F1.
+/// *F1* replaces the answers of a node with no resistance by a pattern.
+/// uses the following chain:
+/// (1) calculate subset s of nodes with resistance <= 0 not in `fixed_nodes`
+/// (2) get MAX (n in s) [sum over questions (delegation_count(n) + 1) * contradiction(n)]
+/// ties go to the node of least identifier.
+/// # return
+/// (node identifier,score)
+pub fn qbot_function_2(w:Array2<usize>,x:Array2<f32>,resistance:Array1<f32>,fixed_nodes:HashSet<usize>) -> Option<(usize,f32)> {
+    let (r,_) = w.dim();
+    let mut n:Option<(usize,f32)> = None;
+    for i in 0..r {
+        if resistance[i] > 0. || fixed_nodes.contains(&i) {
+            continue;
+        }
+
+        let s1:f32 = w.slice(s![i,..]).iter().zip(x.slice(s![i,..]).iter())
+            .map(|(a,b)| (*a + 1) as f32 * b).sum();
+        if n.is_none() || s1 > n.unwrap().1 {
+            n = Some((i,s1));
+        }
+    }
+    n
+}
+
+/// # description
+/// function that determines a batch of at most `k` nodes to fix by F2
+/// with a total cost of at most `budget`.
+/// uses the following chain:
+/// (1) calculate subset s of nodes that were delegates to any other node
+/// (2) cost of n in s is `cost(qbot_base_function(n))`, value of n is its
+///     total delegation count
+/// (3) 0-1 knapsack of maximum value over s
+///
+/// cost of qbot_function_3 is the score, at least 1.
+/// # return
+/// (node identifier,score) of each node of the batch, by identifier
+pub fn qbot_function_3(z:Array2<i32>,w:Array2<usize>,wanted_answers:Array1<i32>,fixed_nodes:HashSet<usize>,
+    k:usize,budget:i32) -> Vec<(usize,i32)> {
+    let doms = vec![(i32::MIN,i32::MAX);z.dim().1];
+    qbot_function_3_domain(z,w,wanted_answers,&doms,fixed_nodes,k,budget,&|x| x.max(1))
+}
+
+/// # description
+/// qbot_function_3 on answers of any domain; `doms` is the answer
+/// domain of each question and `cost` the fuel cost of a score.
+///
+/// the knapsack takes O(q * k * (b + 1)) memory for q delegates and
+/// b = min(`budget`, total cost) <= `MAX_KNAPSACK_BUDGET`; a greater b
+/// divides budget and costs by ceil(b / `MAX_KNAPSACK_BUDGET`), costs
+/// rounded up, so a batch stays within `budget` but may not be the best.
+pub fn qbot_function_3_domain<D:AnsDomain>(z:Array2<D::Ans>,w:Array2<usize>,wanted_answers:Array1<D::Ans>,
+    doms:&Vec<D>,fixed_nodes:HashSet<usize>,k:usize,budget:i32,cost:&dyn Fn(i32) -> i32) -> Vec<(usize,i32)> {
+    let q:Vec<usize> = delegate_nodes(w.clone()).into_iter().filter(|x| !fixed_nodes.contains(&x)).collect();
+    if q.len() == 0 || k == 0 || budget < 0 {
+        return Vec::new();
+    }
+
+    let mut scores:Vec<i32> = Vec::new();
+    let mut costs:Vec<usize> = Vec::new();
+    let mut values:Vec<usize> = Vec::new();
+    for i in q.iter() {
+        let z1:Array1<D::Ans> = z.slice(s![*i,..]).to_owned();
+        let w1:Array1<i32> = w.slice(s![*i,..]).iter().map(|x| *x as i32).collect();
+        let s1 = qbot_base_function_domain(z1,w1,wanted_answers.clone(),doms);
+        scores.push(s1);
+        costs.push(cost(s1).max(0) as usize);
+        values.push(w.slice(s![*i,..]).sum());
+    }
+
+    // best[c][b]: max value of at most c nodes of cost at most b
+    let b = (budget as usize).min(costs.iter().sum());
+    let sc = (b + MAX_KNAPSACK_BUDGET - 1) / MAX_KNAPSACK_BUDGET;
+    let (b,costs) = if sc > 1 {(b / sc,costs.into_iter().map(|x| (x + sc - 1) / sc).collect())} else {(b,costs)};
+    let k = k.min(q.len());
+    let mut best:Vec<Vec<usize>> = vec![vec![0;b + 1];k + 1];
+    let mut keep:Vec<Vec<Vec<bool>>> = vec![vec![vec![false;b + 1];k + 1];q.len()];
+    for i in 0..q.len() {
+        for c in (1..k + 1).rev() {
+            for b2 in (costs[i]..b + 1).rev() {
+                let v = best[c - 1][b2 - costs[i]] + values[i];
+                if v > best[c][b2] {
+                    best[c][b2] = v;
+                    keep[i][c][b2] = true;
+                }
+            }
+        }
+    }
+
+    let mut x:Vec<(usize,i32)> = Vec::new();
+    let (mut c,mut b2) = (k,b);
+    for i in (0..q.len()).rev() {
+        if c > 0 && keep[i][c][b2] {
+            x.push((q[i],scores[i]));
+            c -= 1;
+            b2 -= costs[i];
+        }
+    }
+    x.reverse();
+    x
+}
+
+/// # description
+/// function that ranks nodes by expected share of future delegations.
+/// The share of node n not in `excluded` is
+/// (delegation_count(n) + prior) / sum over such nodes of the same;
+/// nodes of `excluded` (dead or fixed by F2) can not be delegates.
+/// # return
+/// (node identifier,share) by greatest share, ties by identifier
+pub fn qbot_function_4(w:Array2<usize>,excluded:HashSet<usize>,prior:f32) -> Vec<(usize,f32)> {
+    let (r,_) = w.dim();
+    let v:Vec<(usize,f32)> = (0..r).into_iter().filter(|i| !excluded.contains(i))
+        .map(|i| (i,w.slice(s![i,..]).sum() as f32 + prior)).collect();
+    let t:f32 = v.iter().map(|x| x.1).sum();
+    let mut x:Vec<(usize,f32)> = v.into_iter().map(|(i,s1)| (i,if t == 0. {0.} else {s1 / t})).collect();
+    x.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+    x
+}
+
+/// # description
+/// function that determines one edge to sever by fix F3
+/// *F3* removes a delegate from the neighbors of a node so the node
+///        can no longer delegate to it.
+/// uses the following chain:
+/// (1) calculate subset s of edges (node,delegate) of `dl` that are not
+///     severed and have no node in `excluded`
+/// (2) get MAX (e in s) [contradiction charged to delegate through e]
+/// ties go to the least edge; edges of score 0 are not chosen.
+/// # return
+/// (edge,score)
+pub fn qbot_function_5(dl:&HashMap<(usize,usize),(usize,f32)>,excluded:HashSet<usize>,severed:&HashSet<(usize,usize)>) -> Option<((usize,usize),f32)> {
+    let mut e:Vec<(usize,usize)> = dl.keys().filter(|x| !severed.contains(x) &&
+        !excluded.contains(&x.0) && !excluded.contains(&x.1)).cloned().collect();
+    e.sort();
+
+    let mut n:Option<((usize,usize),f32)> = None;
+    for x in e.into_iter() {
+        let s1 = dl[&x].1;
+        if s1 > 0. && (n.is_none() || s1 > n.unwrap().1) {
+            n = Some((x,s1));
+        }
+    }
+    n
+}
+
 /// # description
 /// calculates subset of nodes that were delegates to any other node
 pub fn delegate_nodes(w: Array2<usize>) -> Vec<usize> {
@@ -95,6 +261,59 @@ pub fn qbot_function_1__test_case2() -> (Array2<i32>,Array2<usize>,Array1<i32>)
     (z,w,wa)
 }
 
+pub fn qbot_function_2__test_case1() -> (Array2<usize>,Array2<f32>,Array1<f32>) {
+
+    let w:Array2<usize> = arr2(&[[2,0,1],
+                    [0,0,0],
+                    [3,1,0],
+                    [1,1,1]]);
+
+    let x:Array2<f32> = arr2(&[[0.5,0.,0.2],
+        [0.9,0.9,0.9],
+        [0.4,0.1,0.],
+        [0.3,0.3,0.3]]);
+
+    let r: Array1<f32> = arr1(&[0.,-1.,5.,0.]);
+    (w,x,r)
+}
+
+pub fn qbot_function_3__test_case1() -> (Array2<i32>,Array2<usize>,Array1<i32>) {
+
+    let z:Array2<i32> = arr2(&[[5,0,2],
+        [1,1,1],
+        [4,4,4],
+        [0,0,0],
+        [2,2,9]]);
+
+    let w:Array2<usize> = arr2(&[[2,1,0],
+                    [3,3,3],
+                    [1,0,1],
+                    [0,0,0],
+                    [0,1,4]]);
+
+    let wa: Array1<i32> = arr1(&[1,1,1]);
+    (z,w,wa)
+}
+
+pub fn qbot_function_4__test_case1() -> (Array2<usize>,HashSet<usize>) {
+
+    let w:Array2<usize> = arr2(&[[2,1,0],
+                    [3,3,3],
+                    [1,0,1],
+                    [0,0,0],
+                    [0,1,4]]);
+
+    (w,HashSet::from_iter(vec![1]))
+}
+
+pub fn qbot_function_5__test_case1() -> HashMap<(usize,usize),(usize,f32)> {
+    HashMap::from_iter(vec![((0,1),(4,0.2)),
+        ((0,2),(3,1.5)),
+        ((1,2),(1,0.5)),
+        ((3,2),(2,1.5)),
+        ((3,4),(5,0.))])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,4 +330,70 @@ mod tests {
         let x = qbot_function_1(z,w,wa,HashSet::new());
         assert_eq!(x.unwrap().0,2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test__qbot_function_2() {
+        let (w,x,r) = qbot_function_2__test_case1();
+        let n = qbot_function_2(w.clone(),x.clone(),r.clone(),HashSet::new()).unwrap();
+        assert_eq!(n.0,1);
+        assert!((n.1 - 2.7).abs() < 0.0001);
+
+        // node 2 has resistance
+        assert_eq!(qbot_function_2(w.clone(),x.clone(),r.clone(),HashSet::from_iter(vec![1])).unwrap().0,0);
+        assert_eq!(qbot_function_2(w.clone(),x.clone(),r.clone(),HashSet::from_iter(vec![0,1])).unwrap().0,3);
+        assert!(qbot_function_2(w,x,r,HashSet::from_iter(vec![0,1,3])).is_none());
+    }
+
+    #[test]
+    fn test__qbot_function_3() {
+        // costs 9,1,6,33 and values 3,9,2,5 of nodes 0,1,2,4
+        let (z,w,wa) = qbot_function_3__test_case1();
+        let f = |k:usize,b:i32,fx:Vec<usize>| qbot_function_3(z.clone(),w.clone(),wa.clone(),
+            HashSet::from_iter(fx),k,b);
+
+        assert_eq!(f(2,10,vec![]),vec![(0,9),(1,0)]);
+        assert_eq!(f(3,16,vec![]),vec![(0,9),(1,0),(2,6)]);
+        assert_eq!(f(1,40,vec![]),vec![(1,0)]);
+        assert_eq!(f(2,40,vec![1]),vec![(2,6),(4,33)]);
+        assert_eq!(f(2,0,vec![]),vec![]);
+        assert_eq!(f(0,40,vec![]),vec![]);
+
+        // costs above the knapsack bound are scaled down
+        let doms = vec![(i32::MIN,i32::MAX);3];
+        let x = qbot_function_3_domain(z.clone(),w.clone(),wa.clone(),&doms,HashSet::new(),3,17_000_000,
+            &|x| x.max(1) * 1_000_000);
+        assert_eq!(x,vec![(0,9),(1,0),(2,6)]);
+        let x = qbot_function_3_domain(z.clone(),w.clone(),wa.clone(),&doms,HashSet::new(),1,i32::MAX,
+            &|x| x.max(1) * 1_000_000);
+        assert_eq!(x,vec![(1,0)]);
+    }
+
+    #[test]
+    fn test__qbot_function_4() {
+        let (w,ex) = qbot_function_4__test_case1();
+        let x = qbot_function_4(w.clone(),HashSet::new(),0.);
+        assert_eq!(x.iter().map(|y| y.0).collect::<Vec<usize>>(),vec![1,4,0,2,3]);
+        assert_eq!(x[0].1,9. / 19.);
+        assert_eq!(x[4].1,0.);
+
+        let x = qbot_function_4(w.clone(),ex,1.);
+        assert_eq!(x.iter().map(|y| y.0).collect::<Vec<usize>>(),vec![4,0,2,3]);
+        assert_eq!(x[0].1,6. / 14.);
+        let t:f32 = x.iter().map(|y| y.1).sum();
+        assert!((t - 1.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test__qbot_function_5() {
+        let dl = qbot_function_5__test_case1();
+        let f = |ex:Vec<usize>,sv:Vec<(usize,usize)>| qbot_function_5(&dl,HashSet::from_iter(ex),&HashSet::from_iter(sv));
+
+        // tie of (0,2) and (3,2) goes to the least edge
+        assert_eq!(f(vec![],vec![]),Some(((0,2),1.5)));
+        assert_eq!(f(vec![],vec![(0,2)]),Some(((3,2),1.5)));
+        assert_eq!(f(vec![2],vec![]),Some(((0,1),0.2)));
+
+        // edge of no contradiction is not severed
+        assert_eq!(f(vec![0,2],vec![]),None);
+    }
+}
@@ -1,15 +1,97 @@
 
 //! database for Q.
 //! logs responses of nodes, and sends them 
-use ndarray::{arr1,arr2,Array1,Array2,Dim,s};
-use crate::ans;
-use std::collections::HashSet;
+use ndarray::{Array2,Axis,Dim,s};
+use crate::ans_domain::AnsDomain;
+use crate::id_registry::IdRegistry;
+use std::collections::{HashMap,HashSet};
 use std::fmt;
 
+/// mode of charging the contradiction of a delegated response
+/// to the nodes of the delegation
+#[derive(Clone,Debug,PartialEq)]
+pub enum Attribution {
+    /// equal split over all nodes
+    Equal,
+    /// split by the contradiction of each node's own answer
+    Proportional,
+    /// split by the increase in contradiction of the mean answer
+    /// from adding the node's answer to those of the others
+    LeaveOneOut,
+    /// Shapley value of the contradiction of the mean answer
+    Shapley
+}
+
+/// contradiction of the mean of the answers of `nodes` against `r`;
+/// 0 for no nodes.
+fn coalition_contradiction<D:AnsDomain>(dom:&D,r:&D::Ans,answers:&HashMap<usize,D::Ans>,nodes:&Vec<usize>) -> f32 {
+    if nodes.len() == 0 {
+        return 0.;
+    }
+    let v:Vec<(D::Ans,f32)> = nodes.iter().map(|n| (answers[n].clone(),1.)).collect();
+    dom.contradiction(r,&dom.mean(&v))
+}
+
+/// # description
+/// splits the contradiction of response `resp` against `r` over the nodes
+/// of `answers` by mode `mode`. Negative leave-one-out and Shapley values
+/// are charged as 0, so a node that moves the response towards `r` is not
+/// penalised, and the rest are scaled to sum to the contradiction of
+/// `resp`. Shapley is exact for at most 12 nodes, else leave-one-out.
+pub fn attribute_contradiction<D:AnsDomain>(dom:&D,r:&D::Ans,answers:&HashMap<usize,D::Ans>,resp:&D::Ans,mode:&Attribution) -> HashMap<usize,f32> {
+    let a = dom.contradiction(r,resp);
+    let mut ns:Vec<usize> = answers.keys().cloned().collect();
+    ns.sort();
+    let l = ns.len();
+    let equal: HashMap<usize,f32> = ns.iter().map(|n| (*n,a / l as f32)).collect();
+
+    // scales non-negative scores to sum to a; equal split if all are 0
+    let scale = |sc:Vec<f32>| -> HashMap<usize,f32> {
+        let s:f32 = sc.iter().map(|x| x.max(0.)).sum();
+        if s == 0. {
+            return equal.clone();
+        }
+        ns.iter().zip(sc.iter()).map(|(n,x)| (*n,a * x.max(0.) / s)).collect()
+    };
+
+    match mode {
+        Attribution::Equal => equal.clone(),
+        Attribution::Proportional => scale(ns.iter().map(|n| dom.contradiction(r,&answers[n])).collect()),
+        Attribution::LeaveOneOut => {
+            let vn = coalition_contradiction(dom,r,answers,&ns);
+            scale(ns.iter().map(|n| vn - coalition_contradiction(dom,r,answers,
+                &ns.iter().filter(|x| *x != n).cloned().collect())).collect())
+        },
+        Attribution::Shapley => {
+            if l > 12 {
+                return attribute_contradiction(dom,r,answers,resp,&Attribution::LeaveOneOut);
+            }
+
+            // value of each coalition by bitmask
+            let v:Vec<f32> = (0..1usize << l).map(|m| coalition_contradiction(dom,r,answers,
+                &(0..l).filter(|i| m & (1 << i) != 0).map(|i| ns[i]).collect())).collect();
+            let f:Vec<f32> = (0..l + 1).map(|k| (1..k + 1).map(|x| x as f32).product()).collect();
+
+            scale((0..l).map(|i| {
+                let mut phi:f32 = 0.;
+                for m in 0..1usize << l {
+                    if m & (1 << i) != 0 {
+                        continue;
+                    }
+                    let k = m.count_ones() as usize;
+                    phi += f[k] * f[l - k - 1] / f[l] * (v[m | (1 << i)] - v[m]);
+                }
+                phi
+            }).collect())
+        }
+    }
+}
+
 /// structure for containing Q's data
 /// row is node
 /// column is question
-pub struct QData {
+#[derive(Clone)]
+pub struct QData<A = i32> {
     /// rate of delegation
     pub w: Array2<usize>,
     /// rate of contradiction
@@ -17,18 +99,43 @@ pub struct QData {
     /// duplicates of question asked
     pub y: Array2<usize>,
     /// mean answers of node
-    pub z: Array2<i32> 
+    pub z: Array2<A>,
+    /// (node,question) -> sequence of direct answers of node
+    pub al: HashMap<(usize,usize),Vec<A>>,
+    /// (node,delegate) -> (delegated responses through the edge,
+    /// contradiction charged to delegate through the edge)
+    pub dl: HashMap<(usize,usize),(usize,f32)>,
+    /// external id of each row
+    pub ids: Option<IdRegistry>
 }
 
-pub fn build_QData(r:usize,c:usize) -> QData {
+pub fn build_QData<A:Clone + Default>(r:usize,c:usize) -> QData<A> {
     let w: Array2<usize> = Array2::zeros((r,c));
     let x: Array2<f32> = Array2::zeros((r,c));
     let y: Array2<usize> = Array2::zeros((r,c));
-    let z: Array2<i32> = Array2::zeros((r,c));
-    QData{w:w,x:x,y:y,z:z} 
+    let z: Array2<A> = Array2::default((r,c));
+    QData{w:w,x:x,y:y,z:z,al:HashMap::new(),dl:HashMap::new(),ids:None} 
 }
 
-impl fmt::Display for QData {
+/// matrix `a` with a row of `fv` appended
+pub fn append_row<T:Clone>(a:&Array2<T>,fv:T) -> Array2<T> {
+    let (r,c) = a.dim();
+    Array2::from_shape_fn((r + 1,c),|(i,j)| if i < r {a[[i,j]].clone()} else {fv.clone()})
+}
+
+/// matrix `a` with a column of `fv` appended
+pub fn append_col<T:Clone>(a:&Array2<T>,fv:T) -> Array2<T> {
+    let (r,c) = a.dim();
+    Array2::from_shape_fn((r,c + 1),|(i,j)| if j < c {a[[i,j]].clone()} else {fv.clone()})
+}
+
+/// matrix `a` without row `i`
+pub fn drop_row<T:Clone>(a:&Array2<T>,i:usize) -> Array2<T> {
+    let x:Vec<usize> = (0..a.dim().0).into_iter().filter(|j| *j != i).collect();
+    a.select(Axis(0),&x)
+}
+
+impl<A:fmt::Debug> fmt::Display for QData<A> {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s1 = format!("-- W\n{:?}\n",self.w);
@@ -36,6 +143,9 @@ impl fmt::Display for QData {
         let mut s3 = format!("-- Y\n{:?}\n",self.y);
         let mut s4 = format!("-- Z\n{:?}\n",self.z);
         let mut q = "".to_string();
+        if !self.ids.is_none() {
+            q.push_str(&format!("-- rows\n{:?}\n",self.ids.as_ref().unwrap().ids.iter().map(|x| x.to_string()).collect::<Vec<String>>()));
+        }
         q.push_str(&s1);
         q.push_str(&s2);
         q.push_str(&s3);
@@ -44,57 +154,143 @@ impl fmt::Display for QData {
     }
 }
 
-impl QData {
+impl<A:Clone + PartialEq + Default + fmt::Debug> QData<A> {
+
+    /// # description
+    /// appends an empty row for a new node
+    /// # return
+    /// row of new node
+    pub fn add_row(&mut self) -> usize {
+        self.w = append_row(&self.w,0);
+        self.x = append_row(&self.x,0.);
+        self.y = append_row(&self.y,0);
+        self.z = append_row(&self.z,A::default());
+        self.w.dim().0 - 1
+    }
 
-    /// 
-    pub fn average_ans_to_question(&mut self,qi:usize,ansrange:(i32,i32)) -> i32 {
-        let r1:Array1<f32> = self.z.slice(s![..,qi]).to_owned().into_iter().map(|q| q as f32).collect();
-        let r2:Array1<f32> = self.y.slice(s![..,qi]).to_owned().into_iter().map(|q| q as f32).collect();
-        let a:i32 = 0; 
-        let s = r2.sum();
+    /// # description
+    /// appends an empty column for a new question
+    /// # return
+    /// column of new question
+    pub fn add_col(&mut self) -> usize {
+        self.w = append_col(&self.w,0);
+        self.x = append_col(&self.x,0.);
+        self.y = append_col(&self.y,0);
+        self.z = append_col(&self.z,A::default());
+        self.w.dim().1 - 1
+    }
 
-        // case: no questions asked, use average of range
-        if s == 0. {
-            return ((ansrange.1 + ansrange.0) as f32 / 2.).round() as i32;
-        }
+    /// # description
+    /// removes row `i` of a removed node; rows and logs of greater
+    /// index move down by one.
+    pub fn remove_row(&mut self,i:usize) {
+        self.w = drop_row(&self.w,i);
+        self.x = drop_row(&self.x,i);
+        self.y = drop_row(&self.y,i);
+        self.z = drop_row(&self.z,i);
+        self.al = self.al.drain().filter(|(k,_)| k.0 != i)
+            .map(|(k,v)| (if k.0 > i {(k.0 - 1,k.1)} else {k},v)).collect();
+        let f = |j:usize| if j > i {j - 1} else {j};
+        self.dl = self.dl.drain().filter(|(k,_)| k.0 != i && k.1 != i)
+            .map(|(k,v)| ((f(k.0),f(k.1)),v)).collect();
+    }
+
+    /// # description
+    /// clears the data of column `j` of a retired question
+    pub fn clear_col(&mut self,j:usize) {
+        self.w.slice_mut(s![..,j]).fill(0);
+        self.x.slice_mut(s![..,j]).fill(0.);
+        self.y.slice_mut(s![..,j]).fill(0);
+        self.z.slice_mut(s![..,j]).fill(A::default());
+        self.al.retain(|k,_| k.1 != j);
+    }
+
+    /// y-weighted mean of the mean answers of nodes to question `qi`;
+    /// midpoint of `ansrange` if no node answered.
+    pub fn average_ans_to_question<D:AnsDomain<Ans=A>>(&mut self,qi:usize,ansrange:D) -> A {
+        let r1:Vec<A> = self.z.slice(s![..,qi]).to_owned().into_iter().collect();
+        let r2:Vec<f32> = self.y.slice(s![..,qi]).to_owned().into_iter().map(|q| q as f32).collect();
+        let v:Vec<(A,f32)> = r1.into_iter().zip(r2.into_iter()).filter(|x| x.1 > 0.).collect();
 
-        ((r1 * r2).sum() / s).round() as i32
+        // case: no questions asked, use midpoint of range
+        ansrange.mean(&v)
     }
 
     /// # arguments
     /// wanted_resp := None if Q has known answer else draw from QData 
-    pub fn log_node_response(&mut self,srcidn: usize,nidns:HashSet<usize>,qi:usize, ansrange: (i32,i32),wanted_resp: Option<i32>,resp:i32) -> f32 {
+    pub fn log_node_response<D:AnsDomain<Ans=A>>(&mut self,srcidn: usize,nidns:HashSet<usize>,qi:usize, ansrange: D,wanted_resp: Option<A>,resp:A) -> f32 {
         assert!(nidns.len() > 0);
         let r = if wanted_resp.is_none() {self.average_ans_to_question(qi,ansrange.clone())} else {wanted_resp.unwrap()};
         ////println!("logging node response");
         ////println!("Q answer: {}\tnode response: {}",r,resp);
 
-        let l = nidns.len();
         // calculate contradiction
-        let a = ans::invert_calculate_ans(ansrange,r,resp);
+        let a = ansrange.contradiction(&r,&resp);
         
         // distribute contradiction among all pertinent nodes
         let da = a / nidns.len() as f32;
-        for n in nidns.clone().into_iter() {
-            self.mod_qdata_of_node(n,qi,da);
+        let shares: HashMap<usize,f32> = nidns.into_iter().map(|n| (n,da)).collect();
+        self.log_shares(srcidn,&shares,qi,ansrange,resp);
+        da
+    }
+
+    /// # description
+    /// logs the response `resp` of node `srcidn` that carries the answer of
+    /// each node of its delegation (`answers`, including `srcidn`); the
+    /// contradiction is charged to each node by attribution mode `mode`.
+    /// # return
+    /// node -> charged contradiction
+    pub fn log_node_answers<D:AnsDomain<Ans=A>>(&mut self,srcidn: usize,answers:HashMap<usize,A>,qi:usize, ansrange: D,
+        wanted_resp: Option<A>,resp:A,mode:&Attribution) -> HashMap<usize,f32> {
+        assert!(answers.contains_key(&srcidn));
+        let r = if wanted_resp.is_none() {self.average_ans_to_question(qi,ansrange.clone())} else {wanted_resp.unwrap()};
+        let shares = attribute_contradiction(&ansrange,&r,&answers,&resp,mode);
+        self.log_shares(srcidn,&shares,qi,ansrange,resp);
+        shares
+    }
+
+    /// # description
+    /// charges contradiction `shares` to their nodes, updates delegation count
+    /// of the delegate nodes, and the count and mean answer of `srcidn`.
+    pub fn log_shares<D:AnsDomain<Ans=A>>(&mut self,srcidn: usize,shares:&HashMap<usize,f32>,qi:usize,ansrange: D,resp:A) {
+        let l = shares.len();
+        for (n,da) in shares.iter() {
+            self.mod_qdata_of_node(*n,qi,*da);
         }
 
         // modify delegate nodes count
-        for n in nidns.into_iter() {
-            if n == srcidn {
+        for n in shares.keys() {
+            if *n == srcidn {
                 continue;
             }
-            let d = Dim((n,qi));
+            let d = Dim((*n,qi));
             self.w[d] += l;
         }
 
+        // case: direct answer of node, log it
+        if l == 1 {
+            self.al.entry((srcidn,qi)).or_insert(Vec::new()).push(resp.clone());
+        }
+
         let d = Dim((srcidn,qi));
         self.y[d] += 1; 
 
         // modify mean answer of node
-        self.z[d] = ((self.z[d] as f32 * (self.y[d] - 1) as f32 + resp as f32) / 
-                    (self.y[d] as f32).round()) as i32;
-        da
+        self.z[d] = ansrange.update_mean(&self.z[d],self.y[d] - 1,&resp);
+    }
+
+    /// # description
+    /// logs the delegation edges (node,delegate) of a response with the
+    /// contradiction `shares` charged to each delegate.
+    pub fn log_provenance(&mut self,edges:&Vec<(usize,usize)>,shares:&HashMap<usize,f32>) {
+        for e in edges.iter() {
+            if !shares.contains_key(&e.1) {
+                continue;
+            }
+            let x = self.dl.entry(*e).or_insert((0,0.));
+            x.0 += 1;
+            x.1 += shares[&e.1];
+        }
     }
 
     pub fn mod_qdata_of_node(&mut self,ni:usize,qi:usize,c:f32) {
@@ -104,4 +300,80 @@ impl QData {
         self.x[d] = q2;
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixed_path() -> HashMap<usize,i32> {
+        // node 3 is the source, node 0 honest delegate, node 2 deceptive delegate
+        HashMap::from_iter(vec![(3,50),(0,50),(2,100)])
+    }
+
+    #[test]
+    fn test__attribute_contradiction() {
+        let d = (0,100);
+        let h = mixed_path();
+        let resp = d.mean(&h.values().map(|x| (*x,1.)).collect());
+        assert_eq!(resp,67);
+
+        let e = attribute_contradiction(&d,&50,&h,&resp,&Attribution::Equal);
+        assert!(e[&0] > 0.);
+
+        for m in vec![Attribution::Proportional,Attribution::LeaveOneOut,Attribution::Shapley].into_iter() {
+            let x = attribute_contradiction(&d,&50,&h,&resp,&m);
+            assert_eq!(x[&0],0.,"mode {:?}",m);
+            assert_eq!(x[&3],0.,"mode {:?}",m);
+            assert!(x[&2] > 0.,"mode {:?}",m);
+        }
+
+        // shapley values sum to the contradiction of all nodes
+        let h2:HashMap<usize,i32> = HashMap::from_iter(vec![(0,100),(1,90),(2,80)]);
+        let x = attribute_contradiction(&d,&50,&h2,&90,&Attribution::Shapley);
+        assert!((x.values().sum::<f32>() - 0.8).abs() < 1e-5);
+        assert!(x[&0] > x[&1] && x[&1] > x[&2]);
+
+        // node 1 moves the response towards 50; the others carry it all
+        let h3:HashMap<usize,i32> = HashMap::from_iter(vec![(0,100),(1,0),(2,90)]);
+        let resp = d.mean(&h3.values().map(|x| (*x,1.)).collect());
+        let x = attribute_contradiction(&d,&50,&h3,&resp,&Attribution::Shapley);
+        assert_eq!(x[&1],0.);
+        assert!((x.values().sum::<f32>() - d.contradiction(&50,&resp)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test__QData_log_node_answers() {
+        let d = (0,100);
+        let mut rd:QData<i32> = build_QData(5,1);
+        let s = rd.log_node_answers(3,mixed_path(),0,d,Some(50),67,&Attribution::Shapley);
+        assert_eq!(rd.x[Dim((0,0))],0.);
+        assert_eq!(rd.x[Dim((3,0))],0.);
+        assert_eq!(rd.x[Dim((2,0))],s[&2]);
+        assert_eq!(rd.w[Dim((0,0))],3);
+        assert_eq!(rd.y[Dim((3,0))],1);
+        assert_eq!(rd.z[Dim((3,0))],67);
+
+        // equal split penalises the honest delegate
+        let mut rd2:QData<i32> = build_QData(5,1);
+        rd2.log_node_answers(3,mixed_path(),0,d,Some(50),67,&Attribution::Equal);
+        assert!(rd2.x[Dim((0,0))] > 0.);
+    }
+
+    #[test]
+    fn test__QData_log_provenance() {
+        let mut rd:QData<i32> = build_QData(5,1);
+        let e = vec![(3,0),(3,2),(2,4)];
+        let sh:HashMap<usize,f32> = HashMap::from_iter(vec![(3,0.),(0,0.1),(2,0.5)]);
+        rd.log_provenance(&e,&sh);
+        rd.log_provenance(&e,&sh);
+        assert_eq!(rd.dl[&(3,2)],(2,1.));
+        assert_eq!(rd.dl[&(3,0)],(2,0.2));
+        assert!(!rd.dl.contains_key(&(2,4)));
+
+        // removed node leaves the provenance
+        rd.remove_row(0);
+        assert_eq!(rd.dl.len(),1);
+        assert_eq!(rd.dl[&(2,1)],(2,1.));
+    }
+}
@@ -0,0 +1,177 @@
+//! temporary fixes and node recovery.
+//! Fixes may last a number of rounds, after which Q renews or releases
+//! them; nodes regain resistance each round, and a dead node with
+//! resistance rejoins the network. Recidivism is measured as the rounds
+//! from the release of a node to its next deceptive response.
+use crate::fuel::FuelAction;
+use crate::id_registry::NodeId;
+use std::collections::HashMap;
+use std::fmt;
+
+/// policy of Q on a fix that expires
+#[derive(Clone,Debug,PartialEq)]
+pub enum Renewal {
+    Never,
+    Always,
+    /// renew if the mean contradiction of the restricted node over the
+    /// questions it answered is at least the threshold
+    IfContradicting(f32)
+}
+
+#[derive(Clone,Debug)]
+pub struct Recovery {
+    // resistance regained by each node per round
+    pub regen: f32,
+    // resistance regeneration does not exceed; None for no cap
+    pub max_resistance: Option<f32>,
+    // fuel action of fix -> rounds fix lasts; permanent if absent
+    pub durations: HashMap<FuelAction,usize>,
+    pub renewal: Renewal
+}
+
+/// default: no regeneration, permanent fixes
+pub fn build_Recovery() -> Recovery {
+    Recovery{regen:0.,max_resistance:None,durations:HashMap::new(),renewal:Renewal::Never}
+}
+
+impl Recovery {
+
+    /// round at which a fix of `action` made in `round` expires
+    pub fn expiry(&self,action:FuelAction,round:usize) -> Option<usize> {
+        self.durations.get(&action).map(|d| round + d)
+    }
+
+    /// resistance `r` after one round of regeneration
+    pub fn regenerate(&self,r:f32) -> f32 {
+        let x = r + self.regen;
+        if self.max_resistance.is_none() || self.regen <= 0. {
+            return x;
+        }
+        x.min(self.max_resistance.unwrap().max(r))
+    }
+}
+
+/// node released by an expired fix or by recovery from death
+#[derive(Clone,Debug,PartialEq)]
+pub struct Release {
+    // external id of node; dense indices change when a node is removed
+    pub node: NodeId,
+    pub round: usize,
+    pub kind: String
+}
+
+/// deceptive response of a node after its release
+#[derive(Clone,Debug,PartialEq)]
+pub struct Relapse {
+    pub node: NodeId,
+    pub released: usize,
+    pub round: usize
+}
+
+#[derive(Clone,Debug)]
+pub struct RecidivismLog {
+    // contradiction of a response that is deceptive
+    pub threshold: f32,
+    // node -> round of release without relapse yet
+    pub open: HashMap<NodeId,usize>,
+    pub releases: Vec<Release>,
+    pub relapses: Vec<Relapse>
+}
+
+pub fn build_RecidivismLog(threshold:f32) -> RecidivismLog {
+    RecidivismLog{threshold:threshold,open:HashMap::new(),releases:Vec::new(),relapses:Vec::new()}
+}
+
+impl RecidivismLog {
+
+    pub fn release(&mut self,node:NodeId,round:usize,kind:String) {
+        self.open.insert(node.clone(),round);
+        self.releases.push(Release{node:node,round:round,kind:kind});
+    }
+
+    /// # description
+    /// records contradiction `c` charged to node in `round`.
+    /// # return
+    /// if it is a relapse of a released node
+    pub fn observe(&mut self,node:NodeId,round:usize,c:f32) -> bool {
+        if c < self.threshold || !self.open.contains_key(&node) {
+            return false;
+        }
+        let r = self.open.remove(&node).unwrap();
+        self.relapses.push(Relapse{node:node,released:r,round:round});
+        true
+    }
+
+    /// relapses per release; None for no release
+    pub fn rate(&self) -> Option<f32> {
+        if self.releases.len() == 0 {
+            return None;
+        }
+        Some(self.relapses.len() as f32 / self.releases.len() as f32)
+    }
+
+    /// mean rounds from release to relapse; None for no relapse
+    pub fn mean_delay(&self) -> Option<f32> {
+        if self.relapses.len() == 0 {
+            return None;
+        }
+        Some(self.relapses.iter().map(|x| (x.round - x.released) as f32).sum::<f32>() / self.relapses.len() as f32)
+    }
+
+    /// closes the release of node `id` that is removed from the network;
+    /// its releases and relapses remain.
+    pub fn remove_node(&mut self,id:&NodeId) {
+        self.open.remove(id);
+    }
+}
+
+impl fmt::Display for RecidivismLog {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "releases: {} relapses: {} rate: {:?} mean delay: {:?}\n",self.releases.len(),
+            self.relapses.len(),self.rate(),self.mean_delay())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__RecidivismLog() {
+        let mut l = build_RecidivismLog(0.5);
+        assert!(l.rate().is_none());
+        l.release(NodeId::Num(3),2,"F2 node 3".to_string());
+        l.release(NodeId::Num(4),2,"revived".to_string());
+
+        assert!(!l.observe(NodeId::Num(3),3,0.2));
+        assert!(!l.observe(NodeId::Num(5),3,0.9));
+        assert!(l.observe(NodeId::Num(3),6,0.9));
+        // one relapse per release
+        assert!(!l.observe(NodeId::Num(3),7,0.9));
+
+        assert_eq!(l.rate(),Some(0.5));
+        assert_eq!(l.mean_delay(),Some(4.));
+        assert_eq!(l.relapses[0],Relapse{node:NodeId::Num(3),released:2,round:6});
+
+        l.remove_node(&NodeId::Num(4));
+        assert!(l.open.is_empty());
+        assert_eq!(l.releases[1].node,NodeId::Num(4));
+    }
+
+    #[test]
+    fn test__Recovery_regenerate() {
+        let mut r = build_Recovery();
+        assert_eq!(r.regenerate(-2.),-2.);
+        assert!(r.expiry(FuelAction::F2,3).is_none());
+
+        r.regen = 1.5;
+        r.max_resistance = Some(3.);
+        r.durations.insert(FuelAction::F2,4);
+        assert_eq!(r.regenerate(-2.),-0.5);
+        assert_eq!(r.regenerate(2.),3.);
+        // resistance above the cap is kept
+        assert_eq!(r.regenerate(5.),5.);
+        assert_eq!(r.expiry(FuelAction::F2,3),Some(7));
+    }
+}
@@ -2,18 +2,108 @@ use crate::q_struct;
 use crate::rnetwork;
 use crate::rnode;
 use crate::f1pattern;
+use crate::ans_domain::AnsDomain;
+use crate::est_metrics;
+use crate::classifier;
+use crate::fuel;
+use crate::rndb;
+use crate::multi_q;
+use crate::collusion;
+use crate::node_agent;
+use crate::mcts;
+use crate::qsbf;
+use crate::fix;
+use crate::recovery;
+use crate::node_state::{self,NodeEvent,NodeState};
+use ndarray::Array1;
+use crate::id_registry::NodeId;
 use std::collections::{HashMap,HashSet};
 
-pub struct RNBENV {
-    q: q_struct::QStruct,
-    rn: rnetwork::RNetwork
+/// change of the network or of the questions
+#[derive(Clone)]
+pub enum MembershipEvent<D:AnsDomain = (i32,i32)> {
+    AddNode{id:NodeId,db:rndb::RNDB<D::Ans>,neighbors:Vec<NodeId>,resistance:f32},
+    RemoveNode(NodeId),
+    // answer and objective of every node to the new question
+    AddQuestion{q:q_struct::Q<D>,ans:HashMap<NodeId,(Option<D::Ans>,usize)>,truth:Option<D::Ans>},
+    RetireQuestion(usize)
 }
 
-pub fn build_RNBENV(q:q_struct::QStruct,rn: rnetwork::RNetwork) -> RNBENV {
-    RNBENV{q:q,rn:rn} 
+pub struct RNBENV<D:AnsDomain = (i32,i32)> {
+    // active interrogator
+    q: q_struct::QStruct<D>,
+    rn: rnetwork::RNetwork<D::Ans>,
+
+    // interrogators; slot `active_q` holds a placeholder for `q`
+    interrogators: Vec<q_struct::QStruct<D>>,
+    pub active_q: usize,
+    pub q_sharing: multi_q::QSharing,
+    pub q_turns: multi_q::QTurns,
+    // external ids of nodes killed by each Q
+    pub q_kills: Vec<Vec<NodeId>>,
+
+    // number of answers per question and answer source of F1 patterns
+    pub f1_width: usize,
+    pub f1_src: rnode::F1Source,
+
+    // question -> ground truth; never read by Q 
+    pub ground_truth: HashMap<usize,D::Ans>,
+    pub est_metrics: est_metrics::EstimationMetrics<D::Ans>,
+
+    // classifier of node deception and its evaluation by round
+    pub classifier: Option<classifier::DeceptionClassifier>,
+    pub classifier_evals: Vec<classifier::ClassifierEval>,
+
+    // (round,event) of scripted arrivals and departures
+    pub schedule: Vec<(usize,MembershipEvent<D>)>,
+
+    // groups of colluding nodes
+    pub collusion: Vec<collusion::CollusionGroup<D::Ans>>,
+
+    // nodes whose decisions are made by external code
+    pub controllers: HashMap<NodeId,Box<dyn node_agent::NodeController<D::Ans>>>,
+
+    // lookahead planner of Q's moves; None for `QStruct::one_move`
+    pub planner: Option<mcts::MCTSConfig>,
+
+    // registry of active fixes
+    pub fixes: Vec<fix::ActiveFix<D>>,
+
+    // durations of fixes and regeneration of node resistance
+    pub recovery: recovery::Recovery,
+    pub recidivism: recovery::RecidivismLog,
+    // dead node -> nodes that had it as neighbor
+    dead_links: HashMap<usize,Vec<usize>>
+}
+
+pub fn build_RNBENV<D:AnsDomain>(mut q:q_struct::QStruct<D>,rn: rnetwork::RNetwork<D::Ans>) -> RNBENV<D> {
+    // rows of Q data are the node idns of the network
+    assert_eq!(q.rd.x.dim().0,rn.nodes.len());
+    q.rd.ids = Some(rn.ids.clone());
+    RNBENV{q:q,rn:rn,interrogators:vec![q_struct::build_QStruct(Vec::new(),0,0)],active_q:0,
+        q_sharing:multi_q::QSharing::Cooperative,q_turns:multi_q::QTurns::Interleaved,q_kills:vec![Vec::new()],
+        f1_width:6,f1_src:rnode::F1Source::Objective,
+        ground_truth:HashMap::new(),est_metrics:est_metrics::build_EstimationMetrics(0.05),
+        classifier:None,classifier_evals:Vec::new(),schedule:Vec::new(),collusion:Vec::new(),controllers:HashMap::new(),
+        planner:None,fixes:Vec::new(),recovery:recovery::build_Recovery(),
+        recidivism:recovery::build_RecidivismLog(0.5),dead_links:HashMap::new()} 
 }
 
-impl RNBENV {
+impl<D:AnsDomain> RNBENV<D> {
+
+    /// # description
+    /// copy of environment for simulation; node controllers are not
+    /// copied and the copy has no planner.
+    pub fn clone_sim(&self) -> RNBENV<D> {
+        RNBENV{q:self.q.clone(),rn:self.rn.clone(),interrogators:self.interrogators.clone(),
+            active_q:self.active_q,q_sharing:self.q_sharing.clone(),q_turns:self.q_turns.clone(),
+            q_kills:self.q_kills.clone(),f1_width:self.f1_width,f1_src:self.f1_src.clone(),
+            ground_truth:self.ground_truth.clone(),est_metrics:self.est_metrics.clone(),
+            classifier:self.classifier.clone(),classifier_evals:self.classifier_evals.clone(),
+            schedule:self.schedule.clone(),collusion:self.collusion.clone(),
+            controllers:HashMap::new(),planner:None,fixes:self.fixes.clone(),
+            recovery:self.recovery.clone(),recidivism:self.recidivism.clone(),dead_links:self.dead_links.clone()}
+    }
 
     /// # description
     /// performs summarization on nodes that can no longer
@@ -27,7 +117,16 @@ impl RNBENV {
             //println!("{}",self.q.rd);
             //println!("----");
         }
-        if self.q.c <= 0 {
+
+        // case: every Q is out of fuel
+        let mut fl = vec![self.q.c];
+        for (k,x) in self.interrogators.iter().enumerate() {
+            if k != self.active_q {
+                if verbose {println!("Q{} fuel: {}",k,x.c);}
+                fl.push(x.c);
+            }
+        }
+        if fl.into_iter().all(|x| x <= 0) {
             stat = false; 
         }
 
@@ -35,6 +134,7 @@ impl RNBENV {
         let mut stat2 = false; 
         for i in 0..l {
             if verbose {
+                println!("id {}",self.rn.idn_to_external(i));
                 println!("{}",self.rn.nodes[i]);
                 println!("\t----"); 
             }
@@ -48,72 +148,497 @@ impl RNBENV {
         stat && stat2
     }
 
+    /// # description
+    /// starts round `round` of the fuel model; regenerates Q fuel,
+    /// renews or releases expired fixes and regenerates node resistance.
+    pub fn new_round(&mut self,round:usize) {
+        self.q.fuel.new_round(round,&mut self.q.c);
+        for (k,x) in self.interrogators.iter_mut().enumerate() {
+            if k != self.active_q {
+                x.fuel.new_round(round,&mut x.c);
+            }
+        }
+        self.expire_fixes(round);
+        self.recover_nodes(round);
+    }
+
+    /// # description
+    /// regenerates the resistance of every node; a dead node with
+    /// resistance rejoins the network.
+    /// return: revived nodes
+    pub fn recover_nodes(&mut self,round:usize) -> Vec<usize> {
+        for n in self.rn.nodes.iter_mut() {
+            n.resistance = self.recovery.regenerate(n.resistance);
+        }
+
+        // a node with resistance answers for itself again
+        for ni in 0..self.rn.nodes.len() {
+            if self.is_F1_fixed(ni) && self.rn.nodes[ni].resistance > 0. {
+                self.release_fixes(&|x| x.pattern_node() == Some(ni));
+            }
+        }
+
+        let mut v:Vec<usize> = self.q.dead_nodes.iter().filter(|x| self.rn.nodes[**x].resistance > 0.).cloned().collect();
+        v.sort();
+        for ni in v.iter() {
+            self.revive_node(*ni);
+            let x = self.rn.idn_to_external(*ni).clone();
+            self.recidivism.release(x,round,"revived".to_string());
+        }
+        v
+    }
+
+    /// # description
+    /// node `ni` is no longer dead for any Q; links to it from nodes that
+    /// are alive are restored unless severed by F3.
+    pub fn revive_node(&mut self,ni:usize) {
+        if self.node_state(ni).transition(NodeEvent::Revive).is_none() {
+            return;
+        }
+        self.q.dead_nodes.remove(&ni);
+        for x in self.interrogators.iter_mut() {
+            x.dead_nodes.remove(&ni);
+        }
+
+        let l = self.dead_links.remove(&ni).unwrap_or(Vec::new());
+        for j in l.into_iter() {
+            if self.q.dead_nodes.contains(&j) || self.q.severed.contains(&(j,ni)) {
+                continue;
+            }
+            if !self.rn.nodes[j].neighbors.contains(&ni) {
+                self.rn.nodes[j].neighbors.push(ni);
+            }
+        }
+
+        // links of dead nodes to `ni` are restored on their revival
+    }
+
+    /// round at which a fix of `action` made now expires
+    pub fn fix_expiry(&self,action:fuel::FuelAction) -> Option<usize> {
+        self.recovery.expiry(action,self.q.fuel.round)
+    }
+
+    //////////////////// interrogator functions 
+
+    /// # description
+    /// adds interrogator `q` on the network
+    /// # return
+    /// index of new Q
+    pub fn add_interrogator(&mut self,mut q:q_struct::QStruct<D>) -> usize {
+        assert_eq!(q.rd.x.dim().0,self.rn.nodes.len());
+        q.rd.ids = Some(self.rn.ids.clone());
+        self.interrogators.push(q);
+        self.q_kills.push(Vec::new());
+        self.interrogators.len() - 1
+    }
+
+    pub fn num_interrogators(&self) -> usize {
+        self.interrogators.len()
+    }
+
+    /// # description
+    /// makes Q of index `k` the active interrogator
+    pub fn switch_q(&mut self,k:usize) {
+        assert!(k < self.interrogators.len());
+        if k == self.active_q {
+            return;
+        }
+        std::mem::swap(&mut self.q,&mut self.interrogators[self.active_q]);
+        std::mem::swap(&mut self.q,&mut self.interrogators[k]);
+        self.active_q = k;
+    }
+
+    pub fn fetch_interrogator(&mut self,k:usize) -> &mut q_struct::QStruct<D> {
+        if k == self.active_q {
+            return &mut self.q;
+        }
+        &mut self.interrogators[k]
+    }
+
+    /// outcome of each Q
+    pub fn q_outcomes(&mut self) -> Vec<multi_q::QOutcome> {
+        (0..self.interrogators.len()).into_iter().map(|k| {
+            let x = self.q_kills[k].clone();
+            multi_q::q_outcome(self.fetch_interrogator(k),x)
+        }).collect()
+    }
+
+    /// # description
+    /// one move of every Q with fuel, in the order of `q_turns`;
+    /// Q 0 is active after the round.
+    /// return: if any Q could make a move
+    pub fn execute_round(&mut self,verbose:bool) -> bool {
+        let n = self.interrogators.len();
+        if n == 1 {
+            return self.execute_Q_move(verbose);
+        }
+
+        let mut stat = false;
+        let mut plans: Vec<(usize,(Option<(usize,usize)>,Option<(usize,i32)>))> = Vec::new();
+        for k in 0..n {
+            self.switch_q(k);
+            if self.q.c <= 0 {
+                continue;
+            }
+
+            if verbose {println!("-- Q{}",k);}
+            match self.q_turns {
+                multi_q::QTurns::Interleaved => {
+                    stat = self.execute_Q_move(verbose) || stat;
+                },
+                multi_q::QTurns::Simultaneous => plans.push((k,self.plan_Q_move()))
+            }
+        }
+
+        for (k,(i,i2)) in plans.into_iter() {
+            self.switch_q(k);
+            if verbose {println!("-- Q{}",k);}
+            stat = self.execute_planned_Q_move(i,i2,verbose) || stat;
+        }
+        self.switch_q(0);
+        stat
+    }
+
+    /// # description
+    /// records Q's estimate of each question with a ground truth
+    /// for round `round`.
+    pub fn record_estimation(&mut self,round:usize) {
+        let mut k:Vec<usize> = self.ground_truth.keys().cloned().collect();
+        k.sort();
+        for qi in k.into_iter() {
+            let a = self.q.ans_to_q(qi);
+            let d = self.q.qs[qi].ans_range.clone();
+            self.est_metrics.record(round,qi,&d,a,&self.ground_truth[&qi]);
+        }
+    }
+
+    /// # description
+    /// labels nodes by `classifier` and evaluates the labels against
+    /// node objectives for round `round`.
+    pub fn record_classification(&mut self,round:usize) -> Option<classifier::ClassifierEval> {
+        if self.classifier.is_none() {
+            return None;
+        }
+
+        let h = classifier::classify_all(self.classifier.as_ref().unwrap(),&mut self.q);
+        let e = classifier::evaluate(round,&h,&self.rn);
+        self.classifier_evals.push(e.clone());
+        Some(e)
+    }
+
     /// # description
     /// executes one move by Q
-    pub fn execute_Q_move(&mut self,verbose:bool) {
-        let (i,i2) = self.q.one_move();
+    /// return: if Q could make a move
+    pub fn execute_Q_move(&mut self,verbose:bool) -> bool {
+        let (i,i2) = self.plan_Q_move();
+        self.execute_planned_Q_move(i,i2,verbose)
+    }
+
+    /// # description
+    /// (node,question) pair and F2 target of the next move of Q
+    pub fn plan_Q_move(&mut self) -> (Option<(usize,usize)>,Option<(usize,i32)>) {
+        if !self.planner.is_none() {
+            let c = self.planner.clone().unwrap();
+            return mcts::plan(&c,self);
+        }
+        self.q.one_move()
+    }
+
+    /// # description
+    /// executes the move of Q to query pair `i` and F2 fix `i2`
+    /// return: if Q could make the move
+    pub fn execute_planned_Q_move(&mut self,i:Option<(usize,usize)>,i2:Option<(usize,i32)>,verbose:bool) -> bool {
         if i.is_none() {
-            if verbose {println!("all nodes are dead");}
-            return;
+            if verbose {println!("all nodes are dead or have detected answer patterns");}
+            return false;
+        }
+
+        let (ni,qi) = i.unwrap();
+        let x = self.external_id(Some(ni));
+
+        // case: node can not be queried
+        if !self.node_state(ni).can_be_queried() {
+            if verbose {println!("node {} can not be queried",self.rn.idn_to_external(ni));}
+            return false;
+        }
+
+        // case: Q can not afford the query
+        if !self.q.fuel.can_afford(self.q.c,fuel::FuelAction::Query,0,0) {
+            if verbose {println!("Q can not afford query");}
+            self.q.fuel.charge(&mut self.q.c,fuel::FuelAction::Query,x,0);
+            return false;
         }
 
         if verbose {
-            println!("executing query {} on node {}",i.as_ref().unwrap().1,i.as_ref().unwrap().0);
+            println!("executing query {} on node {}",qi,self.rn.idn_to_external(ni));
+        }
+
+        // fix by F2, keeping fuel for the query
+        let qc = self.q.fuel.cost(fuel::FuelAction::Query,0);
+        if !i2.is_none() && !self.q.fuel.can_afford(self.q.c,fuel::FuelAction::F2,i2.unwrap().1,qc) {
+            if verbose {println!("\tQ can not afford F2 on node {}",self.rn.idn_to_external(i2.unwrap().0));}
+            let n = self.external_id(Some(i2.unwrap().0));
+            self.q.fuel.refuse(self.q.c,fuel::FuelAction::F2,n);
+        } else {
+            self.fix_F2(i2,verbose);
         }
 
-        // fix by F2
-        self.fix_F2(i2,verbose);
+        // sever an edge by F3, keeping fuel for the query
+        let i3 = self.q.F3_target();
+        if !i3.is_none() && !self.q.fuel.can_afford(self.q.c,fuel::FuelAction::F3,i3.unwrap().1.round() as i32,qc) {
+            let n = self.external_id(Some((i3.unwrap().0).0));
+            self.q.fuel.refuse(self.q.c,fuel::FuelAction::F3,n);
+        } else if !i3.is_none() {
+            self.fix_F3(i3,verbose);
+        }
 
         // execute the query
-        self.execute_query_on_node(i.as_ref().unwrap().0,i.as_ref().unwrap().1,verbose);
+        self.execute_query_on_node(ni,qi,verbose);
 
         // update QStruct fuel after executing query
-        self.q.c -= 1; 
+        self.q.fuel.charge(&mut self.q.c,fuel::FuelAction::Query,x,0);
+        true
     }
 
     /// # description
     /// performs an F2 fix on node, node f.0 can no be
-    /// be a delegate
-    pub fn fix_F2(&mut self,f:Option<(usize,i32)>,verbose:bool) {
+    /// be a delegate; the fix is made by `apply_fix`.
+    /// return: if the fix is made
+    pub fn fix_F2(&mut self,f:Option<(usize,i32)>,verbose:bool) -> bool {
         if f.is_none() {
-            return;
+            return false;
         }
 
         let (x1,x2) = f.unwrap();
 
-        // mark node delegate status as false
-        self.q.f2_nodes.insert(x1);
+        // charge the cost of f2 score to Q.c and mark node delegate
+        // status as false
+        if !self.apply_fix(Box::new(fix::F2Fix{node:x1,score:x2,expires:self.fix_expiry(fuel::FuelAction::F2)})) {
+            return false;
+        }
 
         if verbose {
-            println!("\tnode {} is fixed by F2",x1);
+            println!("\tnode {} is fixed by F2",self.rn.idn_to_external(x1));
         }
 
-        // subtract f2 score from Q.c
-        self.q.c -= x2; 
-
         if verbose {
             println!("\tQ fuel is: {}",self.q.c);
         }
+        true
     }
 
     /// # description
-    /// iterates through nodes and apply F1 fix on them.
+    /// applies F1 fix on every exhausted node in the order of
+    /// `qsbf::qbot_function_2`; each fix is charged with the pattern
+    /// width as score and made by `apply_fix`.
     pub fn fix_F1(&mut self) {
         let l = self.rn.nodes.len();
+        let r:Array1<f32> = (0..l).into_iter().map(|i| self.rn.nodes[i].resistance).collect();
+        let mut fx:HashSet<usize> = (0..l).into_iter().filter(|i| self.is_F1_fixed(*i)).collect();
+        loop {
+            let x = qsbf::qbot_function_2(self.q.rd.w.clone(),self.q.rd.x.clone(),r.clone(),fx.clone());
+            if x.is_none() {
+                break;
+            }
+            fx.insert(x.unwrap().0);
+            self.fix_F1_node(x.unwrap().0);
+        }
+    }
 
-        // collect the ansrange vec 
-        let qrvec:Vec<(i32,i32)> = self.q.qs.clone().into_iter().map(|x| x.ans_range).collect();
-        for i in 0..l {
-            // case: node with no resistance not fixed yet
-            let stat = self.rn.nodes[i].f1.is_none() && self.rn.nodes[i].resistance <= 0.; 
-            if stat {
-                let mut f1 = rnode::default_F1_anspattern(&mut self.rn.nodes[i],&mut self.rn.ans_box,qrvec.clone());
-                self.rn.nodes[i].f1 = Some(f1);
+    /// # description
+    /// performs an F3 fix on edge f.0: node f.0.0 can no longer
+    /// delegate to node f.0.1; the fix is made by `apply_fix`.
+    /// return: if the fix is made
+    pub fn fix_F3(&mut self,f:Option<((usize,usize),f32)>,verbose:bool) -> bool {
+        if f.is_none() {
+            return false;
+        }
+        let ((a,b),s1) = f.unwrap();
+        if !self.apply_fix(Box::new(fix::F3Fix{edge:(a,b),score:s1,expires:self.fix_expiry(fuel::FuelAction::F3)})) {
+            return false;
+        }
+
+        if verbose {
+            println!("\tedge {} -> {} is severed by F3",self.rn.idn_to_external(a),self.rn.idn_to_external(b));
+            println!("\tQ fuel is: {}",self.q.c);
+        }
+        true
+    }
+
+    /// # description
+    /// applies F2 fix on the batch of `QStruct::F2_batch`
+    /// return: nodes fixed
+    pub fn fix_F2_batch(&mut self,k:usize,budget:i32,verbose:bool) -> Vec<usize> {
+        let b = self.q.F2_batch(k,budget);
+        b.into_iter().filter(|x| self.fix_F2(Some(*x),verbose)).map(|x| x.0).collect()
+    }
+
+    /// # description
+    /// applies F1 fix on node `ni` if it has no resistance and is not
+    /// fixed yet.
+    /// return: if the fix is made
+    pub fn fix_F1_node(&mut self,ni:usize) -> bool {
+        let w = self.f1_width;
+        let e = self.fix_expiry(fuel::FuelAction::F1);
+        self.apply_fix(Box::new(fix::build_F1Fix(ni,w,e)))
+    }
+
+    //////////////////// fix registry functions
+
+    /// # description
+    /// applies fix `f` if it is applicable and Q can pay for it, and adds
+    /// it to the registry.
+    /// return: if the fix is made
+    pub fn apply_fix(&mut self,mut f:Box<dyn fix::Fix<D>>) -> bool {
+        if !f.applicable(self) {
+            return false;
+        }
+
+        let x = self.external_id(f.target());
+        if !self.q.fuel.charge(&mut self.q.c,f.fuel_action(),x,f.score()) {
+            return false;
+        }
+
+        f.apply(self);
+        let r = self.q.fuel.round;
+        self.fixes.push(fix::ActiveFix{fix:f,q:self.active_q,round:r});
+        true
+    }
+
+    /// # description
+    /// reverts fix `k` of the registry on behalf of the Q that applied it
+    /// and removes it.
+    pub fn revert_fix(&mut self,k:usize) -> fix::ActiveFix<D> {
+        let mut x = self.fixes.remove(k);
+        let aq = self.active_q;
+        self.switch_q(x.q);
+        x.fix.revert(self);
+        self.switch_q(aq);
+        x
+    }
+
+    /// # description
+    /// fixes that expire by round `round` are renewed by `recovery.renewal`
+    /// if the Q of the fix can pay for it, else reverted and their node
+    /// released.
+    /// return: number of fixes reverted
+    pub fn expire_fixes(&mut self,round:usize) -> usize {
+        let mut c = 0;
+        let mut k = 0;
+        while k < self.fixes.len() {
+            if !self.fixes[k].fix.expiry().map_or(false,|x| x <= round) {
+                k += 1;
+                continue;
+            }
+
+            if self.renew_fix(k,round) {
+                k += 1;
+                continue;
             }
+
+            let x = self.revert_fix(k);
+            if !x.fix.restricted().is_none() {
+                let n = self.external_id(x.fix.restricted()).unwrap();
+                self.recidivism.release(n,round,x.fix.name());
+            }
+            c += 1;
+        }
+        c
+    }
+
+    /// # description
+    /// renews fix `k` in round `round` if `recovery.renewal` decides so,
+    /// charging its cost to the Q of the fix.
+    /// return: if the fix is renewed
+    pub fn renew_fix(&mut self,k:usize,round:usize) -> bool {
+        let n = self.fixes[k].fix.restricted();
+        let stat = match self.recovery.renewal {
+            recovery::Renewal::Never => false,
+            recovery::Renewal::Always => true,
+            recovery::Renewal::IfContradicting(t) => n.map_or(false,|x| self.node_contradiction(x) >= t)
+        };
+        if !stat {
+            return false;
+        }
+
+        let a = self.fixes[k].fix.fuel_action();
+        let (tg,s) = (self.external_id(self.fixes[k].fix.target()),self.fixes[k].fix.score());
+        let aq = self.active_q;
+        self.switch_q(self.fixes[k].q);
+        let stat2 = self.q.fuel.charge(&mut self.q.c,a,tg,s);
+        self.switch_q(aq);
+        if stat2 {
+            let e = self.recovery.expiry(a,round);
+            self.fixes[k].fix.set_expiry(e);
+        }
+        stat2
+    }
+
+    /// mean contradiction of node `ni` over the questions it answered
+    /// or was a delegate for
+    pub fn node_contradiction(&self,ni:usize) -> f32 {
+        let (_,c) = self.q.rd.x.dim();
+        let v:Vec<f32> = (0..c).into_iter().filter(|j| self.q.rd.y[[ni,*j]] > 0 || self.q.rd.w[[ni,*j]] > 0)
+            .map(|j| self.q.rd.x[[ni,j]]).collect();
+        if v.len() == 0 {
+            return 0.;
+        }
+        v.iter().sum::<f32>() / v.len() as f32
+    }
+
+    /// answer of node `ni` to question `qi` under the active fixes; None
+    /// if no fix changes it.
+    pub fn fix_answer(&mut self,ni:usize,qi:usize) -> Option<D::Ans> {
+        self.fixes.iter_mut().find_map(|x| x.fix.answer(ni,qi))
+    }
+
+    /// if node `ni` answers by an F1 pattern of the registry
+    pub fn is_F1_fixed(&self,ni:usize) -> bool {
+        self.fixes.iter().any(|x| x.fix.pattern_node() == Some(ni))
+    }
+
+    /// state of node `ni` by its resistance, death for Q and fixes
+    pub fn node_state(&self,ni:usize) -> NodeState {
+        let n = &self.rn.nodes[ni];
+        node_state::build_NodeState(n.resistance <= 0.,self.q.dead_nodes.contains(&ni),self.is_F1_fixed(ni),
+            !self.fix_allows_delegate(ni))
+    }
+
+    /// # description
+    /// reverts the fixes that satisfy `f`
+    /// return: number of fixes reverted
+    pub fn release_fixes(&mut self,f:&dyn Fn(&dyn fix::Fix<D>) -> bool) -> usize {
+        let mut c = 0;
+        let mut k = 0;
+        while k < self.fixes.len() {
+            if f(self.fixes[k].fix.as_ref()) {
+                self.revert_fix(k);
+                c += 1;
+            } else {
+                k += 1;
+            }
+        }
+        c
+    }
+
+    /// if node `ni` can answer as a delegate under the active fixes
+    pub fn fix_allows_delegate(&self,ni:usize) -> bool {
+        self.fixes.iter().all(|x| x.fix.allows_delegate(ni))
+    }
+
+    /// applies the log filters of the active fixes to the answers of a
+    /// response of node `srcidn`
+    pub fn fix_filter_log(&self,srcidn:usize,answers:&mut HashMap<usize,D::Ans>) {
+        for x in self.fixes.iter() {
+            x.fix.filter_log(srcidn,answers);
         }
     }
 
     pub fn execute_query_on_node(&mut self,ni:usize,qi:usize,verbose:bool) {
+        if !self.node_state(ni).can_be_queried() {
+            return;
+        }
+
         // have node perform delegation trial
         self.node_delegation(ni,qi,verbose);
         let eni = self.rn.node_idn_to_index(ni);
@@ -125,10 +650,10 @@ impl RNBENV {
 
         // fetch node ans
         let qr = self.q.qs[qi].ans_range.clone();
-        let mut na = self.rn.nodes[eni].ans_to_q(&mut self.rn.ans_box,qi,qr.clone());
+        let mut na = self.node_ans_to_q(ni,qi);
 
         // fetch delegation ans 
-        let da = self.rn.nodes[eni].update_sat_map(qi,qr,na,self.rn.c);
+        let da = self.rn.nodes[eni].update_sat_map(qi,qr.clone(),na.clone(),self.rn.c);
         if verbose {
             println!("delegation answer: {:?}",da);
             println!("DB after delegation:\n*************\n{}\n******************\n\n",self.rn.nodes[eni].db);
@@ -137,54 +662,69 @@ impl RNBENV {
         // fetch Q ans
         let qa = self.q.ans_to_q(qi); 
         if verbose {
-            println!("Q answer: {}",qa);
+            println!("Q answer: {:?}",qa);
         }
 
         let mut dp = self.rn.nodes[eni].db.delegation_path.clone().unwrap();
         if da.is_none() {
             dp.dscore = Some(f32::MAX); 
         } else {
-            let rd = (qa - da.unwrap()).abs() as f32;        
+            let rd = qr.distance(&qa,da.as_ref().unwrap());        
             dp.dscore = Some(rd);
         }
 
         self.rn.nodes[eni].db.delegation_path = Some(dp);
 
         // let node decide
-        let node_del = self.rn.nodes[eni].choose_to_delegate(qi);
+        let mut node_del = self.rn.nodes[eni].choose_to_delegate(qi);
+        node_del = self.controlled_choose_to_delegate(ni,qi).unwrap_or(node_del) && !da.is_none();
         if verbose {
-            println!("node {} will delegate: {}",ni,node_del);
+            println!("node {} will delegate: {}",self.rn.idn_to_external(ni),node_del);
 
             if node_del {
                 println!("{}",self.rn.nodes[eni].db.delegation_path.clone().unwrap());
             }
         }
 
-        // let Q respond
-        let mut nidns:HashSet<usize> = HashSet::new();
+        // let Q respond; response carries the answer of each node
+        let mut answers:HashMap<usize,D::Ans> = HashMap::new();
             // case: delegation 
         if node_del {
-            nidns = HashSet::from_iter(
-                self.rn.nodes[eni].db.delegation_path.as_ref().unwrap().na.clone().into_keys());
+            answers = self.rn.nodes[eni].db.delegation_path.as_ref().unwrap().na.clone();
+            answers.insert(ni,na.clone());
             na = da.unwrap();
         } else {
             // case: no delegation, restore node db
             self.rn.nodes[eni].db = db2;
+            answers.insert(ni,na.clone());
+        }
+
+        // delegation edges the response travelled
+        let mut edges:Vec<(usize,usize)> = Vec::new();
+        if node_del {
+            for (a,v) in self.rn.nodes[eni].db.delegation_path.as_ref().unwrap().sm.iter() {
+                edges.extend(v.iter().filter(|b| answers.contains_key(b) && answers.contains_key(a)).map(|b| (*a,*b)));
+            }
+            edges.sort();
         }
 
-        nidns.insert(ni);
-        self.execute_Q_response_to_nodeset(ni,nidns,qi,na);
+        self.fix_filter_log(ni,&mut answers);
+        answers.retain(|k,_| self.node_state(*k).can_be_logged());
+        self.execute_Q_response_to_answers(ni,answers,&edges,qi,na.clone());
 
         // update node resistance
-        let rd = (qa - na).abs() as f32;
+        let rd = qr.distance(&qa,&na);
+
+        // colluding node may pass the loss to the member on turn
+        let ani = if node_del {eni} else {self.resistance_absorber(ni)};
 
         // CAUTION: bug fix here 
         if !node_del {
-            self.rn.nodes[eni].resistance = self.rn.nodes[eni].resistance - rd; 
+            self.rn.nodes[ani].resistance = self.rn.nodes[ani].resistance - rd; 
         }
 
         if verbose {
-            println!("node {} answer: {}",ni,na); 
+            println!("node {} answer: {:?}",self.rn.idn_to_external(ni),na); 
         }
 
         if verbose {
@@ -198,8 +738,8 @@ impl RNBENV {
         }
 
         // update dead nodes in Q
-        if self.rn.nodes[eni].resistance <= 0. {
-            self.update_dead_node(ni);
+        if self.rn.nodes[ani].resistance <= 0. {
+            self.update_dead_node(ani);
         }
 
         // update feedback map for node ni 
@@ -211,14 +751,38 @@ impl RNBENV {
         }
     }
 
-    pub fn execute_Q_response_to_nodeset(&mut self,srcidn:usize,node_set:HashSet<usize>,qi:usize,nodeset_ans:i32) {
+    pub fn execute_Q_response_to_nodeset(&mut self,srcidn:usize,node_set:HashSet<usize>,qi:usize,nodeset_ans:D::Ans) {
         // have q respond to nodeset answer
         self.q.response_to_nodeset(srcidn,node_set,qi,nodeset_ans);
     }
+
+    pub fn execute_Q_response_to_answers(&mut self,srcidn:usize,answers:HashMap<usize,D::Ans>,edges:&Vec<(usize,usize)>,qi:usize,nodeset_ans:D::Ans) {
+        // case: cooperative Qs log the responses of each other
+        if self.q_sharing == multi_q::QSharing::Cooperative {
+            for (k,x) in self.interrogators.iter_mut().enumerate() {
+                if k != self.active_q {
+                    x.response_to_delegation(srcidn,answers.clone(),edges,qi,nodeset_ans.clone());
+                }
+            }
+        }
+
+        // contradiction of each node's own answer, for relapses
+        let wanted = self.q.ans_to_q(qi);
+        let dom = self.q.qs[qi].ans_range.clone();
+        let r = self.q.fuel.round;
+        for (n,a) in answers.iter() {
+            let x = self.rn.idn_to_external(*n).clone();
+            self.recidivism.observe(x,r,dom.contradiction(&wanted,a));
+        }
+
+        // have q respond to nodeset answer with the answers of each node
+        self.q.response_to_delegation(srcidn,answers,edges,qi,nodeset_ans);
+    }
     
     //////////////////// node delegation functions 
 
     pub fn node_delegation(&mut self,ni:usize,qi:usize,verbose:bool) {
+        self.apply_vouching(qi);
         self.node_delegation_on_query(ni,qi,verbose);
         self.prompt_node_delegate_answers(ni,qi,verbose);
     }
@@ -228,15 +792,16 @@ impl RNBENV {
     /// for print-to-screen. 
     pub fn node_delegation_on_query(&mut self,ni:usize,qi:usize,verbose:bool) {
         if verbose {
-            println!("delegate travel for src {} question {}",ni,qi);
+            println!("delegate travel for src {} question {}",self.rn.idn_to_external(ni),qi);
         }
         
         // instantiate delegation
         let eni = self.rn.node_idn_to_index(ni);
-        self.rn.nodes[eni].delegate(qi);
+        self.rn.nodes[eni].start_delegation();
 
         // delegate
         let mut x = self.rn.nodes[eni].db.clone();
+        self.node_delegate_one(eni,&mut x,qi);
         let mut l = x.delegation_path.as_ref().unwrap().next_ref.len();
         while l > 0 {
             // clear next ref
@@ -253,7 +818,7 @@ impl RNBENV {
             // collect values for next ref 
             for r_ in r.into_iter() {
                 let ni2 = self.rn.node_idn_to_index(r_);
-                self.rn.nodes[ni2].delegate_one(&mut x,qi);
+                self.node_delegate_one(ni2,&mut x,qi);
             }
             l = x.delegation_path.as_ref().unwrap().next_ref.len();
         }
@@ -268,7 +833,7 @@ impl RNBENV {
         
         c.insert(si);
         if verbose {
-            println!("delegate answers for src {} question {}",si,qi);
+            println!("delegate answers for src {} question {}",self.rn.idn_to_external(si),qi);
         }
 
         // get delegation path of source
@@ -282,8 +847,8 @@ impl RNBENV {
             let esi = self.rn.node_idn_to_index(e0);
             q = q[1..].to_vec();
 
-            // case: node e0 is in QStruct.f2_nodes
-            if self.q.f2_nodes.contains(&e0) {
+            // case: node e0 can not answer as a delegate (F2 or dead)
+            if !self.node_state(e0).can_delegate() {
                 l = q.len();
                 continue;
             }
@@ -300,13 +865,11 @@ impl RNBENV {
             // add node answer if node not head
             if e0 != dp.head {
                 // let node answer
-                let ans = self.rn.nodes[esi].ans_to_q(&mut self.rn.ans_box,
-                    qi,qr.clone());
-                dp.na.insert(e0,ans);
-
+                let ans = self.node_ans_to_q(e0,qi);
                 if verbose {
-                    println!("node {} answer {}",e0,ans);
+                    println!("node {} answer {:?}",self.rn.idn_to_external(e0),ans);
                 }
+                dp.na.insert(e0,ans);
             }
 
             // add its qualifying neighbors to cache
@@ -325,19 +888,287 @@ impl RNBENV {
         self.rn.nodes[esi0].db.delegation_path = Some(dp);
     }
 
-    pub fn fetch_node(&mut self,ni:usize) -> &mut rnode::RNBNode {
+    pub fn fetch_node(&mut self,ni:usize) -> &mut rnode::RNBNode<D::Ans> {
         let eni = self.rn.node_idn_to_index(ni);
         &mut self.rn.nodes[eni]
     }
 
-    pub fn fetch_QStruct(&mut self) -> &mut q_struct::QStruct {
+    /// node of external id; None if id is not registered
+    pub fn fetch_node_by_id(&mut self,id:&crate::id_registry::NodeId) -> Option<&mut rnode::RNBNode<D::Ans>> {
+        let x = self.rn.external_to_idn(id);
+        if x.is_none() {
+            return None;
+        }
+        Some(&mut self.rn.nodes[x.unwrap()])
+    }
+
+    /// external id of node `ni`, for logs that outlive the dense index
+    pub fn external_id(&self,ni:Option<usize>) -> Option<crate::id_registry::NodeId> {
+        ni.map(|x| self.rn.idn_to_external(x).clone())
+    }
+
+    //////////////////// node controller functions 
+
+    /// # description
+    /// local observation of node `ni` for question `qi`
+    pub fn node_observation(&self,ni:usize,qi:usize) -> node_agent::NodeObservation<D::Ans> {
+        let n = &self.rn.nodes[ni];
+        node_agent::build_NodeObservation(ni,self.q.fuel.round,&n.db,&n.neighbors,n.resistance,qi)
+    }
+
+    /// # description
+    /// adds the delegates of node `ni` for question `qi` to the delegation
+    /// path of `db`; a controlled node chooses among its candidates.
+    pub fn node_delegate_one(&mut self,ni:usize,db:&mut rndb::RNDB<D::Ans>,qi:usize) {
+        let id = self.rn.idn_to_external(ni).clone();
+        if self.controllers.contains_key(&id) {
+            let c = self.rn.nodes[ni].delegate_candidates(db);
+            let o = self.node_observation(ni,qi);
+            let v = self.controllers.get_mut(&id).unwrap().delegates(&o,qi,&c);
+            if !v.is_none() {
+                let v2 = v.unwrap().into_iter().filter(|x| c.contains(x)).collect();
+                self.rn.nodes[ni].delegate_to(db,v2);
+                return;
+            }
+        }
+        self.rn.nodes[ni].delegate_one(db,qi);
+    }
+
+    /// # description
+    /// decision of the controller of node `ni` to delegate question `qi`;
+    /// None if node is not controlled or controller uses the built-in choice.
+    pub fn controlled_choose_to_delegate(&mut self,ni:usize,qi:usize) -> Option<bool> {
+        let id = self.rn.idn_to_external(ni).clone();
+        if !self.controllers.contains_key(&id) {
+            return None;
+        }
+
+        let db = &self.rn.nodes[ni].db;
+        let ds = db.delegation_path.as_ref().unwrap().dscore.unwrap_or(f32::MAX);
+        let rf = db.rfeedback.get(&qi).and_then(|x| x.last()).cloned().unwrap_or(0.);
+        let o = self.node_observation(ni,qi);
+        self.controllers.get_mut(&id).unwrap().delegate(&o,qi,ds,rf)
+    }
+
+    //////////////////// collusion functions 
+
+    /// # description
+    /// answer of node `ni` to question `qi`; a node without a fix answer
+    /// by its controller if any, else a colluding node answers the shared
+    /// answer of its group.
+    pub fn node_ans_to_q(&mut self,ni:usize,qi:usize) -> D::Ans {
+        let qr = self.q.qs[qi].ans_range.clone();
+
+        // case: answer set by a fix
+        let fa = self.fix_answer(ni,qi);
+        if !fa.is_none() {
+            return qr.nearest(&fa.unwrap());
+        }
+
+        // case: answer of controller
+        let id = self.rn.idn_to_external(ni).clone();
+        if self.controllers.contains_key(&id) {
+            let o = self.node_observation(ni,qi);
+            let a = self.controllers.get_mut(&id).unwrap().answer(&o,qi);
+            if !a.is_none() {
+                return qr.nearest(&a.unwrap());
+            }
+        }
+
+        let g = collusion::group_of(&self.collusion,self.rn.idn_to_external(ni));
+        if !g.is_none() && self.collusion[g.unwrap()].fake_ans.contains_key(&qi) {
+            return qr.nearest(&self.collusion[g.unwrap()].fake_ans[&qi]);
+        }
+        self.rn.nodes[ni].indep_ans_to_q(&mut self.rn.ans_box,qi,qr)
+    }
+
+    /// # description
+    /// members of each group hold satisfaction rate `vouch` of each other
+    /// for question `qi`, so that they delegate to each other.
+    pub fn apply_vouching(&mut self,qi:usize) {
+        for g in self.collusion.iter() {
+            let m:Vec<usize> = g.members.iter().filter_map(|x| self.rn.external_to_idn(x)).collect();
+            for i in m.iter() {
+                for j in m.iter() {
+                    if i == j {
+                        continue;
+                    }
+                    self.rn.nodes[*i].db.sat_other.entry(*j).or_insert(HashMap::new()).insert(qi,g.vouch);
+                }
+            }
+        }
+    }
+
+    /// # description
+    /// node that absorbs the resistance loss of a direct answer of `ni`;
+    /// the member on turn if `ni` is in a rotating group.
+    pub fn resistance_absorber(&mut self,ni:usize) -> usize {
+        let g = collusion::group_of(&self.collusion,self.rn.idn_to_external(ni));
+        if g.is_none() || !self.collusion[g.unwrap()].rotate {
+            return ni;
+        }
+
+        let alive:HashSet<NodeId> = self.collusion[g.unwrap()].members.iter().filter(|x| {
+            let i = self.rn.external_to_idn(x);
+            !i.is_none() && self.node_state(i.unwrap()).has_resistance()
+        }).cloned().collect();
+        let x = self.collusion[g.unwrap()].next_absorber(&alive);
+        if x.is_none() {
+            return ni;
+        }
+        self.rn.external_to_idn(&x.unwrap()).unwrap()
+    }
+
+    //////////////////// membership functions 
+
+    /// # description
+    /// adds node of external id `id` with answer database `db`; `db` has
+    /// an answer and objective for every question.
+    /// # return
+    /// idn of new node
+    pub fn add_node(&mut self,id:NodeId,db:rndb::RNDB<D::Ans>,neighbors:Vec<NodeId>,resistance:f32) -> usize {
+        for qi in 0..self.q.qs.len() {
+            assert!(db.ans.contains_key(&qi) && db.obj.contains_key(&qi), "node {} has no answer to question {}",id,qi);
+        }
+
+        let i = self.rn.add_node(id,db,neighbors,resistance);
+        let i2 = self.q.add_node_row();
+        assert_eq!(i,i2);
+        self.q.rd.ids = Some(self.rn.ids.clone());
+        for (k,x) in self.interrogators.iter_mut().enumerate() {
+            if k != self.active_q {
+                x.add_node_row();
+                x.rd.ids = Some(self.rn.ids.clone());
+            }
+        }
+        i
+    }
+
+    /// # description
+    /// removes node of external id `id` with its edges and data; node
+    /// idns greater than its idn move down by one.
+    pub fn remove_node(&mut self,id:&NodeId) -> Option<rnode::RNBNode<D::Ans>> {
+        let x = self.rn.external_to_idn(id);
+        if x.is_none() {
+            return None;
+        }
+
+        let n = self.rn.remove_node(x.unwrap());
+        let ni = x.unwrap();
+        let f = move |i:usize| if i == ni {None} else if i > ni {Some(i - 1)} else {Some(i)};
+        self.fixes.retain_mut(|y| y.fix.remap_nodes(&f));
+        self.recidivism.remove_node(id);
+        self.dead_links = self.dead_links.drain().filter_map(|(k,v)| f(k).map(|k2| (k2,v.into_iter().filter_map(|y| f(y)).collect()))).collect();
+        self.q.remove_node_row(x.unwrap());
+        self.q.rd.ids = Some(self.rn.ids.clone());
+        for (k,x2) in self.interrogators.iter_mut().enumerate() {
+            if k != self.active_q {
+                x2.remove_node_row(x.unwrap());
+                x2.rd.ids = Some(self.rn.ids.clone());
+            }
+        }
+        Some(n)
+    }
+
+    /// # description
+    /// adds question `q`; `ans` has the answer and objective of every
+    /// node, and `truth` is the ground truth of `q` if any.
+    /// # return
+    /// index of new question
+    pub fn add_question(&mut self,q:q_struct::Q<D>,ans:HashMap<NodeId,(Option<D::Ans>,usize)>,truth:Option<D::Ans>) -> usize {
+        for (k,x) in self.interrogators.iter_mut().enumerate() {
+            if k != self.active_q {
+                x.add_question(q.clone());
+            }
+        }
+        let qi = self.q.add_question(q);
+        for n in self.rn.nodes.iter_mut() {
+            let id = self.rn.ids.id(n.idn);
+            assert!(ans.contains_key(id), "node {} has no answer to question {}",id,qi);
+            let (a,o) = ans[id].clone();
+            n.db.add_question(qi,a,o);
+        }
+
+        if !truth.is_none() {
+            self.ground_truth.insert(qi,truth.unwrap());
+        }
+        qi
+    }
+
+    /// # description
+    /// retires question `qi` from Q and from the records of nodes
+    pub fn retire_question(&mut self,qi:usize) {
+        self.q.retire_question(qi);
+        for (k,x) in self.interrogators.iter_mut().enumerate() {
+            if k != self.active_q {
+                x.retire_question(qi);
+            }
+        }
+        for n in self.rn.nodes.iter_mut() {
+            n.db.retire_question(qi);
+        }
+        self.ground_truth.remove(&qi);
+    }
+
+    /// # description
+    /// applies the scheduled events of round `round`
+    /// # return
+    /// number of events applied
+    pub fn apply_schedule(&mut self,round:usize) -> usize {
+        let mut ev:Vec<MembershipEvent<D>> = Vec::new();
+        let mut rest:Vec<(usize,MembershipEvent<D>)> = Vec::new();
+        for (r,e) in self.schedule.drain(..) {
+            if r == round {ev.push(e);} else {rest.push((r,e));}
+        }
+        self.schedule = rest;
+
+        let l = ev.len();
+        for e in ev.into_iter() {
+            match e {
+                MembershipEvent::AddNode{id,db,neighbors,resistance} => {
+                    self.add_node(id,db,neighbors,resistance);
+                },
+                MembershipEvent::RemoveNode(id) => {
+                    self.remove_node(&id);
+                },
+                MembershipEvent::AddQuestion{q,ans,truth} => {
+                    self.add_question(q,ans,truth);
+                },
+                MembershipEvent::RetireQuestion(qi) => self.retire_question(qi)
+            }
+        }
+        l
+    }
+
+    pub fn fetch_QStruct(&mut self) -> &mut q_struct::QStruct<D> {
         &mut self.q
     }
 
+    pub fn fetch_network(&mut self) -> &mut rnetwork::RNetwork<D::Ans> {
+        &mut self.rn
+    }
+
     /// # description
-    /// clears dead nodes from each active node's neighbors
+    /// node `ni` is dead for every Q: its F2 fixes are released and it is
+    /// cleared from each active node's neighbors
     pub fn update_dead_node(&mut self,ni:usize) {
-        self.q.dead_nodes.insert(ni);
+        if self.node_state(ni).transition(NodeEvent::Kill).is_none() {
+            return;
+        }
+        self.release_fixes(&|x| x.fuel_action() == fuel::FuelAction::F2 && x.target() == Some(ni));
+
+        // the kill is of the active Q
+        if self.q.dead_nodes.insert(ni) {
+            let x = self.rn.idn_to_external(ni).clone();
+            self.q_kills[self.active_q].push(x);
+        }
+        let l:Vec<usize> = (0..self.rn.nodes.len()).into_iter().filter(|j| self.rn.nodes[*j].neighbors.contains(&ni)).collect();
+        self.dead_links.entry(ni).or_insert(Vec::new()).extend(l);
+        for (k,x) in self.interrogators.iter_mut().enumerate() {
+            if k != self.active_q {
+                x.dead_nodes.insert(ni);
+            }
+        }
 
         for x in self.rn.nodes.iter_mut() {
             (*x).delete_neighbor(ni);
@@ -346,10 +1177,8 @@ impl RNBENV {
 
     }
 
-    /// # description
-    /// 
-    pub fn is_dead_node(&mut self) {
-
+    pub fn is_dead_node(&self,ni:usize) -> bool {
+        !self.node_state(ni).is_alive()
     }
 
 }
@@ -357,20 +1186,70 @@ impl RNBENV {
 pub fn sample_RNBENV1() -> RNBENV {
     let q = q_struct::sample_QStruct1();
     let r = rnetwork::sample_RNBNetwork1();
+    let mut e = build_RNBENV(q,r);
+
+    // Q does not know the answer to question 1
+    e.ground_truth.insert(1,40);
+    e.classifier = Some(classifier::DeceptionClassifier::Threshold{honest:0.2,erratic:0.1});
+    e
+}
+
+/// two interrogators on sample_RNBNetwork1: Q0 asks questions 0,1,2
+/// and Q1 asks questions 2,3,4 with less fuel. 
+pub fn sample_RNBENV_multi1() -> RNBENV {
+    let q = multi_q::build_interrogator(q_struct::sample_QStruct1(),HashSet::from_iter(vec![0,1,2]));
+    let mut q1 = multi_q::build_interrogator(q_struct::sample_QStruct1(),HashSet::from_iter(vec![2,3,4]));
+    q1.c = 1000;
+    let mut e = build_RNBENV(q,rnetwork::sample_RNBNetwork1());
+    e.add_interrogator(q1);
+    e
+}
+
+/// sample_RNBENV1 with deceptive nodes 2,3,8 in a rotating
+/// collusion group.
+pub fn sample_RNBENV_collusion1() -> RNBENV {
+    let mut e = sample_RNBENV1();
+    let fa = HashMap::from_iter(vec![(0,100),(1,-80),(2,-50),(3,0),(4,0)]);
+    e.collusion.push(collusion::build_CollusionGroup(vec![NodeId::Num(2),NodeId::Num(3),NodeId::Num(8)],
+        fa,1.,true));
+    e
+}
+
+pub fn sample_RNBENV_mixed1() -> RNBENV<crate::ans_domain::MixedDomain> {
+    let q = q_struct::sample_QStruct_mixed1();
+    let r = rnetwork::sample_RNBNetwork_mixed1();
     build_RNBENV(q,r)
 }
 
 /// have Respondent Network Bot run until one of the following:
 /// 1. all nodes in Respondent Network are fixed by F1.
 /// 2. Q runs out of fuel. 
-pub fn run_rnb(r: &mut RNBENV) {
+/// 3. Q has no node left to query.
+pub fn run_rnb<D:AnsDomain>(r: &mut RNBENV<D>) {
     let mut stat:bool = (*r).summarize_stats(true);
     let mut c = 0; 
     while stat {
         println!("ROUND {}",c);
-        (*r).execute_Q_move(true);
-        stat = (*r).summarize_stats(true);
+        (*r).new_round(c);
+        (*r).apply_schedule(c);
+        stat = (*r).execute_round(true);
+        (*r).record_estimation(c);
+        (*r).record_classification(c);
+        stat = (*r).summarize_stats(true) && stat;
         c += 1;
     }
+    print!("{}",(*r).q.fuel);
+    if (*r).num_interrogators() > 1 {
+        for (k,x) in (*r).q_outcomes().into_iter().enumerate() {
+            println!("Q{} {}",k,x);
+        }
+    }
+    print!("{}",(*r).est_metrics);
+    if (*r).recidivism.releases.len() > 0 {
+        print!("{}",(*r).recidivism);
+    }
+    if (*r).classifier_evals.len() > 0 {
+        print!("{}",(*r).classifier_evals.last().unwrap());
+    }
 }
 
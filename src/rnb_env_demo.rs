@@ -42,6 +42,11 @@ pub fn RData_soln__execute_query_on_node_10() -> (Array2<f32>,Array2<usize>,Arra
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::id_registry::NodeId;
+    use crate::q_struct;
+    use crate::ans_domain::{AnsDomain,AnsValue};
+    use crate::node_state::NodeState;
+    use std::collections::{HashMap,HashSet};
 
     #[test]
     fn test_RNBENV_execute_query_on_node__question0() {
@@ -124,5 +129,540 @@ mod tests {
         */
     }
 
+    #[test]
+    fn test_RNBENV_mixed_answer_domains() {
+        let mut r = rnb_env::sample_RNBENV_mixed1();
+
+        // node 0 is honest on the categorical question
+        r.execute_query_on_node(0,2,false);
+        let q = r.fetch_QStruct();
+        assert_eq!(q.rd.y[Dim((0,2))],1);
+        assert_eq!(q.rd.z[Dim((0,2))],AnsValue::Cat(0));
+        assert_eq!(q.rd.x[Dim((0,2))],0.);
+
+        // node 1 lies on the vector question
+        r.execute_query_on_node(1,3,false);
+        let q = r.fetch_QStruct();
+        assert_eq!(q.rd.y[Dim((1,3))],1);
+        assert!(q.rd.x[Dim((1,3))] > 0.);
+
+        // unknown probability is estimated from answers 
+        r.execute_query_on_node(0,1,false);
+        let q = r.fetch_QStruct();
+        let a = q.ans_to_q(1);
+        assert!(q.qs[1].ans_range.contains(&a));
+
+        // every move stays in the answer domains
+        for _ in 0..40 {
+            r.execute_Q_move(false);
+        }
+        r.fix_F1();
+        let q = r.fetch_QStruct();
+        assert!(q.c < 500);
+    }
+
+    #[test]
+    fn test_RNBENV_record_estimation() {
+        let mut r = rnb_env::sample_RNBENV1();
+
+        // ground truth is not Q's answer
+        assert!(r.fetch_QStruct().qs[1].qa.is_none());
+        r.record_estimation(0);
+        let x = r.est_metrics.last(1).unwrap();
+        assert_eq!(x.est,0);
+        assert_eq!(x.abs_err,40.);
+        assert_eq!(x.norm_err,0.25);
+        assert_eq!(r.est_metrics.stabilized_round(1),None);
+
+        // node 0 answers the truth
+        r.execute_query_on_node(0,1,false);
+        r.record_estimation(1);
+        assert_eq!(r.est_metrics.last(1).unwrap().abs_err,0.);
+        assert_eq!(r.est_metrics.stabilized_round(1),Some(1));
+        assert!(!r.est_metrics.history.contains_key(&0));
+    }
+
+    #[test]
+    fn test_RNBENV_record_classification() {
+        let mut r = rnb_env::sample_RNBENV1();
+        for i in 0..20 {
+            r.execute_Q_move(false);
+            let e = r.record_classification(i).unwrap();
+            let s:usize = e.confusion.iter().map(|x| x.iter().sum::<usize>()).sum();
+            assert!(s > 0);
+        }
+        assert_eq!(r.classifier_evals.len(),20);
+        assert_eq!(r.classifier_evals[19].round,19);
+
+        r.classifier = None;
+        assert!(r.record_classification(20).is_none());
+    }
+
+    #[test]
+    fn test_RNBENV_fuel_model() {
+        let mut r = rnb_env::sample_RNBENV1();
+        {
+            let q = r.fetch_QStruct();
+            q.c = 3;
+            q.fuel.refuse_unaffordable = true;
+            q.fuel.f2 = crate::fuel::CostRule::Fixed(10);
+        }
+
+        // F2 fixes are refused, queries are charged until fuel runs out
+        let mut i = 0;
+        while r.execute_Q_move(false) {
+            i += 1;
+        }
+        let q = r.fetch_QStruct();
+        assert_eq!(i,3);
+        assert_eq!(q.c,0);
+        assert!(q.f2_nodes.is_empty());
+        assert_eq!(q.fuel.spent(crate::fuel::FuelAction::Query),3);
+        assert_eq!(q.fuel.log.iter().filter(|x| !x.accepted && x.action == crate::fuel::FuelAction::Query).count(),1);
+        let n = q.fuel.refused();
+
+        // regeneration allows another query; the F2 it can not afford is
+        // logged as refused
+        q.fuel.regen = 1;
+        r.new_round(1);
+        let (i,_) = r.plan_Q_move();
+        assert!(r.execute_planned_Q_move(i,Some((4,5)),false));
+        let q = r.fetch_QStruct();
+        let x = q.fuel.log.iter().rev().find(|x| x.action == crate::fuel::FuelAction::F2).unwrap();
+        assert!(!x.accepted && x.node == Some(NodeId::Num(4)) && x.delta == 0);
+        assert_eq!(q.fuel.refused(),n + 1);
+        assert_eq!(q.c,0);
+    }
+
+    #[test]
+    fn test_RNBENV_fix_F2_batch() {
+        let mut r = rnb_env::sample_RNBENV1();
+        {
+            // nodes 2,4,7 delegated 3,2,1 times
+            let q = r.fetch_QStruct();
+            q.rd.w[[2,0]] = 2;
+            q.rd.w[[2,1]] = 1;
+            q.rd.w[[4,1]] = 2;
+            q.rd.w[[7,2]] = 1;
+        }
+
+        // F2 fixes cost 10; budget of two fixes picks the two most
+        // delegated nodes
+        r.fetch_QStruct().fuel.f2 = crate::fuel::CostRule::Fixed(10);
+        let c = r.fetch_QStruct().c;
+        let d = r.fetch_QStruct().delegation_ranking(0.);
+        let x = r.fix_F2_batch(3,25,false);
+        let q = r.fetch_QStruct();
+        assert_eq!(x,vec![2,4]);
+        assert_eq!(q.c,c - 20);
+        assert_eq!(q.f2_nodes,HashSet::from_iter(vec![2,4]));
+
+        // fixed nodes leave the ranking of future delegates
+        let d2 = q.delegation_ranking(0.);
+        assert_eq!(d2.len(),d.len() - x.len());
+        assert!(d2.iter().all(|n| !x.contains(&n.0)));
+    }
+
+    #[test]
+    fn test_RNBENV_fix_F2_charge() {
+        let mut r = rnb_env::sample_RNBENV1();
+        {
+            let q = r.fetch_QStruct();
+            q.rd.w[[4,1]] = 2;
+            q.rd.w[[6,1]] = 2;
+            q.dead_nodes.insert(6);
+        }
+        let c = r.fetch_QStruct().c;
+
+        // no charge for a node that is dead, already fixed or never a delegate
+        assert!(!r.fix_F2(Some((6,5)),false));
+        assert!(!r.fix_F2(Some((1,5)),false));
+        assert!(r.fix_F2(Some((4,5)),false));
+        assert!(!r.fix_F2(Some((4,5)),false));
+
+        let q = r.fetch_QStruct();
+        assert_eq!(q.c,c - 5);
+        assert_eq!(q.f2_nodes,HashSet::from_iter(vec![4]));
+        assert_eq!(q.fuel.log.len(),1);
+    }
+
+    #[test]
+    fn test_RNBENV_fix_F3() {
+        let mut r = rnb_env::sample_RNBENV1();
+        let b = r.fetch_node(0).neighbors[0];
+
+        // node 0 delegates to its neighbor b
+        r.node_delegation_on_query(0,0,false);
+        assert!(r.fetch_node(0).db.delegation_path.as_ref().unwrap().sm[&0].contains(&b));
+
+        // Q observed contradiction through edge (0,b) and severs it on its move
+        {
+            let q = r.fetch_QStruct();
+            q.rd.dl.insert((0,b),(2,0.8));
+            q.rd.dl.insert((b,0),(1,0.1));
+            q.f3_min_score = Some(0.5);
+        }
+        let c = r.fetch_QStruct().c;
+        assert!(r.execute_planned_Q_move(Some((1,1)),None,false));
+        let q = r.fetch_QStruct();
+        assert_eq!(q.severed,HashSet::from_iter(vec![(0,b)]));
+        assert_eq!(q.c,c - 2);
+        assert_eq!(q.fuel.spent(crate::fuel::FuelAction::F3),1);
+        assert_eq!(crate::multi_q::q_outcome(q,vec![]).fuel_spent,2);
+
+        // edge below the minimum score is kept
+        assert!(q.F3_target().is_none());
+
+        // delegation path of node 0 no longer has b as a delegate of 0
+        assert!(!r.fetch_node(0).neighbors.contains(&b));
+        r.node_delegation_on_query(0,0,false);
+        assert!(!r.fetch_node(0).db.delegation_path.as_ref().unwrap().sm[&0].contains(&b));
+
+        // severed edge can not be severed again
+        assert!(!r.fix_F3(Some(((0,b),0.8)),false));
+        assert_eq!(r.fetch_QStruct().fuel.spent(crate::fuel::FuelAction::F3),1);
+    }
+
+    #[test]
+    fn test_RNBENV_recovery() {
+        let mut r = rnb_env::sample_RNBENV1();
+        r.recovery.durations.insert(crate::fuel::FuelAction::F2,2);
+        r.fetch_QStruct().rd.w[[4,1]] = 2;
+        let c = r.fetch_QStruct().c;
+
+        // F2 fix of round 0 expires in round 2 and node 4 is released
+        assert!(r.fix_F2(Some((4,5)),false));
+        assert_eq!(r.fixes[0].fix.expiry(),Some(2));
+        r.new_round(1);
+        assert_eq!(r.fixes.len(),1);
+        r.new_round(2);
+        assert_eq!(r.fixes.len(),0);
+        assert!(!r.fetch_QStruct().f2_nodes.contains(&4));
+        assert_eq!(r.recidivism.open,HashMap::from_iter(vec![(NodeId::Num(4),2)]));
+
+        // renewed fix is charged again
+        r.recovery.renewal = crate::recovery::Renewal::Always;
+        assert!(r.fix_F2(Some((4,5)),false));
+        r.new_round(4);
+        assert_eq!(r.fixes.len(),1);
+        assert_eq!(r.fixes[0].fix.expiry(),Some(6));
+        assert_eq!(r.fetch_QStruct().c,c - 15);
+
+        // node 4 relapses on a deceptive response
+        assert!(r.recidivism.observe(NodeId::Num(4),5,0.9));
+        assert_eq!(r.recidivism.rate(),Some(1.));
+        assert_eq!(r.recidivism.mean_delay(),Some(3.));
+
+        // released delegate relapses on its own answer in a response,
+        // though its share of the contradiction is small
+        r.recidivism.release(NodeId::Num(5),4,"F2 node 5".to_string());
+        let answers:HashMap<usize,i32> = HashMap::from_iter(vec![(0,50),(1,50),(5,0)]);
+        r.execute_Q_response_to_answers(0,answers,&Vec::new(),0,50);
+        assert!(r.fetch_QStruct().rd.x[[5,0]] < 0.5);
+        assert_eq!(r.recidivism.relapses.last().unwrap().node,NodeId::Num(5));
+        assert!(r.recidivism.open.get(&NodeId::Num(5)).is_none());
+
+        // dead node 2 regains resistance and rejoins its neighbors
+        let l:Vec<usize> = (0..11).into_iter().filter(|j| r.fetch_node(*j).neighbors.contains(&2)).collect();
+        assert!(l.len() > 0);
+        r.fetch_node(2).resistance = -0.5;
+        r.update_dead_node(2);
+        r.recovery.regen = 1.;
+        assert_eq!(r.recover_nodes(6),vec![2]);
+        assert!(!r.fetch_QStruct().dead_nodes.contains(&2));
+        for j in l.into_iter() {
+            assert!(r.fetch_node(j).neighbors.contains(&2));
+        }
+        assert_eq!(r.recidivism.releases.last().unwrap().kind,"revived".to_string());
+
+        // the log keeps the ids of nodes after a node is removed
+        r.remove_node(&NodeId::Num(0));
+        assert_eq!(r.recidivism.releases[0].node,NodeId::Num(4));
+        assert_eq!(r.recidivism.relapses[0].node,NodeId::Num(4));
+        assert_eq!(r.recidivism.open,HashMap::from_iter(vec![(NodeId::Num(2),6)]));
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_RNBENV_node_state() {
+        let mut r = rnb_env::sample_RNBENV1();
+        assert_eq!(r.node_state(4),NodeState::Active);
+        r.fetch_QStruct().rd.w[[4,1]] = 2;
+        assert!(r.fix_F2(Some((4,5)),false));
+        assert_eq!(r.node_state(4),NodeState::F2Restricted);
+
+        // death releases F2; a dead node is not queried or fixed by F1 twice
+        r.fetch_node(4).resistance = -1.;
+        assert_eq!(r.node_state(4),NodeState::ExhaustedF2Restricted);
+        r.update_dead_node(4);
+        assert_eq!(r.node_state(4),NodeState::Dead);
+        assert!(r.fetch_QStruct().f2_nodes.is_empty());
+        assert!(r.is_dead_node(4));
+        let y = r.fetch_QStruct().rd.y.clone();
+        assert!(!r.execute_planned_Q_move(Some((4,0)),None,false));
+        r.execute_query_on_node(4,0,false);
+        assert_eq!(r.fetch_QStruct().rd.y,y);
+        assert!(r.fix_F1_node(4));
+        assert_eq!(r.node_state(4),NodeState::DeadF1Fixed);
+        assert!(!r.fix_F1_node(4));
+
+        // exhausted node fixed by F1 still answers as a delegate
+        let b = r.fetch_node(0).neighbors[0];
+        r.fetch_node(b).resistance = -1.;
+        assert!(r.fix_F1_node(b));
+        assert_eq!(r.node_state(b),NodeState::F1Fixed);
+        r.node_delegation(0,0,false);
+        assert!(r.fetch_node(0).db.delegation_path.as_ref().unwrap().na.contains_key(&b));
+    }
+
+    #[test]
+    fn test_RNBENV_node_state_property() {
+        // no dead node is queried, a delegate or logged, even when
+        // neighbor lists still hold it
+        for seed in 0..6 {
+            crate::std_rng::set_seed(seed);
+            let mut r = rnb_env::sample_RNBENV1();
+            if seed % 2 == 1 {
+                r.fetch_QStruct().selector = crate::info_gain::NQSelector::InfoGain(crate::info_gain::build_InfoGainModel());
+            }
+            r.fetch_QStruct().dead_nodes.insert(seed as usize + 1);
+            for k in 0..11 {
+                r.fetch_node(k).resistance = r.fetch_node(k).resistance.min(30.);
+            }
+
+            for _ in 0..40 {
+                let d:Vec<usize> = r.fetch_QStruct().dead_nodes.iter().cloned().collect();
+                let (y,w) = (r.fetch_QStruct().rd.y.clone(),r.fetch_QStruct().rd.w.clone());
+                let l:Vec<usize> = d.iter().map(|x| r.fetch_QStruct().rd.al.iter().filter(|(k,_)| k.0 == *x).map(|(_,v)| v.len()).sum()).collect();
+                r.execute_Q_move(false);
+
+                let q = r.fetch_QStruct();
+                for (i,x) in d.iter().enumerate() {
+                    assert_eq!(q.rd.y.slice(s![*x,..]),y.slice(s![*x,..]));
+                    assert_eq!(q.rd.w.slice(s![*x,..]),w.slice(s![*x,..]));
+                    assert_eq!(q.rd.al.iter().filter(|(k,_)| k.0 == *x).map(|(_,v)| v.len()).sum::<usize>(),l[i]);
+                }
+                for x in d.iter() {
+                    assert!(r.is_dead_node(*x));
+                }
+            }
+        }
+
+        // with no contradiction left, the pair of highest contradiction is
+        // still a candidate; no candidate gives no pair
+        let mut r = rnb_env::sample_RNBENV1();
+        let q = r.fetch_QStruct();
+        q.rd.y.fill(1);
+        q.dead_nodes.insert(0);
+        q.retired_qs.insert(1);
+        let c = q.candidate_nq_pairs();
+        assert_eq!(q.max_contra_nq_pair(),Some((1,0)));
+        assert!(c.contains(&q.priority_nq_pair().unwrap()));
+        q.retired_qs = (0..5).collect();
+        assert_eq!(q.max_contra_nq_pair(),None);
+        assert_eq!(q.priority_nq_pair(),None);
+        assert!(!r.execute_Q_move(false));
+    }
+
+    #[test]
+    fn test_RNBENV_membership() {
+        // node 7 may die in unseeded moves and leave the neighbors of node 3
+        crate::std_rng::set_seed(1);
+        let mut r = rnb_env::sample_RNBENV1();
+        for _ in 0..15 {
+            r.execute_Q_move(false);
+        }
+
+        // remove node 2; node 3 becomes idn 2
+        let x = r.fetch_QStruct().rd.x.clone();
+        let n = r.remove_node(&NodeId::Num(2)).unwrap();
+        assert_eq!(n.idn,2);
+        assert!(r.remove_node(&NodeId::Num(2)).is_none());
+        let q = r.fetch_QStruct();
+        assert_eq!(q.rd.x.dim(),(10,5));
+        assert_eq!(q.rd.x.slice(s![2,..]),x.slice(s![3,..]));
+        for k in q.rd.al.keys() {
+            assert!(k.0 < 10);
+        }
+        for i in 0..10 {
+            let nd = r.fetch_node(i);
+            assert_eq!(nd.idn,i);
+            assert!(nd.neighbors.iter().all(|y| *y < 10));
+            assert!(nd.db.sat_other.keys().all(|y| *y < 10));
+            for v in nd.db.delegation_records.d1.values() {
+                assert!(v.keys().all(|y| *y < 10));
+            }
+        }
+        assert_eq!(r.fetch_node(2).neighbors,vec![0,6]);
+
+        // new node linked to node 0 
+        let db = r.fetch_node(0).db.clone();
+        let mut db2 = crate::rndb::build_RNDB(db.ans,db.obj);
+        db2.rfeedback.insert(0,vec![1.]);
+        let i = r.add_node("new".into(),db2,vec![NodeId::Num(0)],50.);
+        assert_eq!(i,10);
+        assert!(r.fetch_node(0).neighbors.contains(&10));
+        assert_eq!(r.fetch_QStruct().rd.y.dim(),(11,5));
+
+        // new question of every node, then retire question 0
+        let mut ans: HashMap<NodeId,(Option<i32>,usize)> = HashMap::new();
+        for j in 0..11 {
+            let id = if j == 10 {NodeId::from("new")} else if j < 2 {NodeId::Num(j as u64)} else {NodeId::Num(j as u64 + 1)};
+            ans.insert(id,(Some(3),0));
+        }
+        let qi = r.add_question(q_struct::Q{qa:None,ans_range:(0,5)},ans,Some(3));
+        assert_eq!(qi,5);
+        assert_eq!(r.ground_truth[&5],3);
+        r.execute_query_on_node(10,5,false);
+        assert_eq!(r.fetch_QStruct().rd.y[Dim((10,5))],1);
+
+        r.retire_question(0);
+        assert!(r.fetch_QStruct().rd.y.slice(s![..,0]).iter().all(|y| *y == 0));
+        assert!(!r.fetch_node(10).db.rfeedback.contains_key(&0));
+        for _ in 0..30 {
+            if !r.execute_Q_move(false) {
+                break;
+            }
+            let q = r.fetch_QStruct();
+            assert!(q.rd.y.slice(s![..,0]).iter().all(|y| *y == 0));
+        }
+    }
+
+    #[test]
+    fn test_RNBENV_external_ids() {
+        // node 3 becomes idn 2; logs keep its external id
+        let mut r = rnb_env::sample_RNBENV1();
+        r.remove_node(&NodeId::Num(2)).unwrap();
+        assert!(r.execute_planned_Q_move(Some((2,0)),None,false));
+        let x = r.fetch_QStruct().fuel.log.last().unwrap().clone();
+        assert_eq!(x.node,Some(NodeId::Num(3)));
+
+        r.update_dead_node(2);
+        let o = r.q_outcomes();
+        assert_eq!(o[0].kills,1);
+        assert_eq!(o[0].killed,vec![NodeId::Num(3)]);
+    }
+
+    #[test]
+    fn test_RNBENV_apply_schedule() {
+        let mut r = rnb_env::sample_RNBENV1();
+        r.schedule.push((2,rnb_env::MembershipEvent::RemoveNode(NodeId::Num(5))));
+        r.schedule.push((1,rnb_env::MembershipEvent::RetireQuestion(4)));
+        r.schedule.push((2,rnb_env::MembershipEvent::RemoveNode(NodeId::Num(7))));
+
+        assert_eq!(r.apply_schedule(0),0);
+        assert_eq!(r.apply_schedule(1),1);
+        assert!(r.fetch_QStruct().retired_qs.contains(&4));
+        assert_eq!(r.apply_schedule(2),2);
+        assert_eq!(r.fetch_QStruct().rd.w.dim(),(9,5));
+        assert!(r.schedule.is_empty());
+    }
+
+    #[test]
+    fn test_RNBENV_multiple_interrogators() {
+        // a queried node that delegates adds to w instead of y
+        crate::std_rng::set_seed(2);
+
+        // cooperative: each Q logs the queries of the other
+        let mut r = rnb_env::sample_RNBENV_multi1();
+        assert_eq!(r.num_interrogators(),2);
+        for _ in 0..5 {
+            assert!(r.execute_round(false));
+        }
+        assert_eq!(r.active_q,0);
+        let y0 = r.fetch_interrogator(0).rd.y.clone();
+        let y1 = r.fetch_interrogator(1).rd.y.clone();
+        assert_eq!(y0,y1);
+        assert_eq!(y0.sum(),10);
+
+        let o = r.q_outcomes();
+        assert_eq!(o[0].queries,5);
+        assert_eq!(o[1].queries,5);
+        assert_eq!(o[1].fuel_left,1000 - o[1].fuel_spent);
+
+        // competitive: each Q only has data of its own questions
+        let mut r = rnb_env::sample_RNBENV_multi1();
+        r.q_sharing = crate::multi_q::QSharing::Competitive;
+        r.q_turns = crate::multi_q::QTurns::Simultaneous;
+        for _ in 0..5 {
+            r.execute_round(false);
+        }
+        let y0 = r.fetch_interrogator(0).rd.y.clone();
+        let y1 = r.fetch_interrogator(1).rd.y.clone();
+        assert_eq!(y0.slice(s![..,3..]).sum(),0);
+        assert_eq!(y1.slice(s![..,0..2]).sum(),0);
+        assert_eq!(y0.sum() + y1.sum(),10);
+
+        // node state is shared
+        r.switch_q(1);
+        let n = r.fetch_node(0).resistance;
+        r.switch_q(0);
+        assert_eq!(r.fetch_node(0).resistance,n);
+    }
+
+    #[test]
+    fn test_RNBENV_collusion() {
+        let mut r = rnb_env::sample_RNBENV_collusion1();
+
+        // members answer the shared answer
+        assert_eq!(r.node_ans_to_q(3,0),100);
+        assert_eq!(r.node_ans_to_q(8,2),-50);
+
+        // members vouch for each other
+        r.apply_vouching(1);
+        assert_eq!(r.fetch_node(2).db.sat_other[&8][&1],1.);
+        assert!(!r.fetch_node(2).db.sat_other.contains_key(&0));
+
+        // members take turns absorbing resistance loss
+        assert_eq!(r.resistance_absorber(8),2);
+        assert_eq!(r.resistance_absorber(8),3);
+        assert_eq!(r.resistance_absorber(0),0);
+        r.fetch_node(8).resistance = 0.;
+        assert_eq!(r.resistance_absorber(2),2);
+
+        // vouching members turn up as delegates, and Q spends its fuel on
+        // F2 fixes of delegates until it runs out in round 12; alone, the
+        // nodes cost Q only its queries and a member dies
+        let c = crate::collusion::compare_collusion(&rnb_env::sample_RNBENV_collusion1,30,1);
+        assert_eq!((c.rounds_with,c.rounds_alone),(12,30));
+        assert_eq!((c.fuel_with,c.fuel_alone),(2786,30));
+        assert_eq!((c.member_fuel_with,c.member_fuel_alone),(412,8));
+        assert_eq!((c.dead_with,c.dead_alone),(0,1));
+        assert_eq!(c.extra_fuel(),2756);
+
+        // the same scenario with seed 0 gives Q nothing to fix either way
+        let c = crate::collusion::compare_collusion(&rnb_env::sample_RNBENV_collusion1,30,0);
+        assert_eq!((c.fuel_with,c.fuel_alone),(30,30));
+        assert_eq!(c.extra_fuel(),0);
+    }
+
+    #[test]
+    fn test_RNBENV_node_controllers() {
+        let mut r = rnb_env::sample_RNBENV1();
+        let s0 = crate::node_agent::ScriptedNode{answers:HashMap::from_iter(vec![(0,0)]),
+            delegate:Some(false),only:None};
+        let s1 = crate::node_agent::ScriptedNode{answers:HashMap::new(),delegate:Some(true),only:Some(vec![7])};
+        r.controllers.insert(NodeId::Num(0),Box::new(s0));
+        r.controllers.insert(NodeId::Num(1),Box::new(s1));
+
+        // node 0 answers 0 instead of 50 and does not delegate
+        r.execute_query_on_node(0,0,false);
+        assert_eq!(r.fetch_node(0).resistance,150.);
+        assert_eq!(r.fetch_QStruct().rd.z[Dim((0,0))],0);
+        assert_eq!(r.fetch_QStruct().rd.w.sum(),0);
+
+        // node 1 delegates to node 7 only
+        r.node_delegation(1,0,false);
+        let dp = r.fetch_node(1).db.delegation_path.clone().unwrap();
+        assert_eq!(dp.sm[&1],vec![7]);
+
+        r.execute_query_on_node(1,0,false);
+        assert_eq!(r.fetch_node(1).resistance,200.);
+        assert!(r.fetch_QStruct().rd.w[Dim((7,0))] > 0);
+
+        let o = r.node_observation(1,0);
+        assert_eq!(o.neighbors,vec![0,7,9]);
+        assert_eq!(o.neighbor_sat[&9],o.db.sat_other[&9][&0]);
+        assert!(o.neighbor_sat.values().all(|x| *x <= 1.));
+        assert_eq!(o.resistance,200.);
+    }
+}
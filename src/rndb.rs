@@ -6,9 +6,9 @@ use crate::df;
 use std::fmt;
 
 #[derive(Clone)]
-pub struct RNDB {
+pub struct RNDB<A = i32> {
     // actual answers of node
-    pub ans: HashMap<usize,Option<i32>>,
+    pub ans: HashMap<usize,Option<A>>,
 
     // objective for each question
     // question id -> 0|1|2
@@ -19,7 +19,7 @@ pub struct RNDB {
     pub sat_other: HashMap<usize,HashMap<usize,f32>>,
 
     // current path used for delegation
-    pub delegation_path: Option<df::DPath>,
+    pub delegation_path: Option<df::DPath<A>>,
 
     // question -> (delegate node -> contradiction)
     pub delegation_records: df::DelegationRecord,
@@ -31,12 +31,47 @@ pub struct RNDB {
 
 /*
 */ 
-pub fn build_RNDB(ans: HashMap<usize,Option<i32>>,obj: HashMap<usize,usize>) -> RNDB {
+pub fn build_RNDB<A>(ans: HashMap<usize,Option<A>>,obj: HashMap<usize,usize>) -> RNDB<A> {
     RNDB{ans:ans,obj:obj,sat_other:HashMap::new(),delegation_path:None,
         delegation_records: df::DelegationRecord{d1:HashMap::new(),d2:HashMap::new()},rfeedback:HashMap::new()}
 }
 
-impl fmt::Display for RNDB {
+impl<A> RNDB<A> {
+
+    /// # description
+    /// registers answer and objective of node for new question `qi`.
+    pub fn add_question(&mut self,qi:usize,ans:Option<A>,obj:usize) {
+        self.ans.insert(qi,ans);
+        self.obj.insert(qi,obj);
+    }
+
+    /// # description
+    /// clears the delegation and feedback records of question `qi`.
+    pub fn retire_question(&mut self,qi:usize) {
+        self.rfeedback.remove(&qi);
+        self.delegation_records.d1.remove(&qi);
+        self.delegation_records.d2.remove(&qi);
+        for v in self.sat_other.values_mut() {
+            v.remove(&qi);
+        }
+    }
+
+    /// # description
+    /// renames node idns of records by `f`; records of nodes mapped to
+    /// None are dropped. The delegation path is cleared.
+    pub fn remap_nodes(&mut self,f:&dyn Fn(usize) -> Option<usize>) {
+        self.sat_other = self.sat_other.drain().filter_map(|(k,v)| f(k).map(|k2| (k2,v))).collect();
+        for v in self.delegation_records.d1.values_mut() {
+            *v = v.drain().filter_map(|(k,x)| f(k).map(|k2| (k2,x))).collect();
+        }
+        for v in self.delegation_records.d2.values_mut() {
+            *v = v.drain().filter_map(|(k,x)| f(k).map(|k2| (k2,x))).collect();
+        }
+        self.delegation_path = None;
+    }
+}
+
+impl<A:fmt::Debug> fmt::Display for RNDB<A> {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s1 = format!("actual answers\n{:?}\n",self.ans);
@@ -2,36 +2,97 @@ use crate::rnode;
 use crate::df;
 use crate::ans;
 use crate::rndb;
+use crate::ans_domain::AnsValue;
+use crate::id_registry::{self,IdRegistry,NodeId};
 use std::collections::HashMap;
 
-pub struct RNetwork {
-    pub nodes: Vec<rnode::RNBNode>,
+#[derive(Clone)]
+pub struct RNetwork<A = i32> {
+    // node of idn i is nodes[i]
+    pub nodes: Vec<rnode::RNBNode<A>>,
+    // external id of each node idn
+    pub ids: IdRegistry,
     pub ans_box: ans::Ansbox,
     pub c: f32
 }
 
-pub fn build_RNetwork(m: HashMap<usize,Vec<usize>>,rndbvec:HashMap<usize,rndb::RNDB>,
-        r:HashMap<usize,f32>,c:f32) -> RNetwork {
-    let mut nodes:Vec<rnode::RNBNode> = Vec::new();
-    for (k,v) in m.into_iter() {
-        let rn = rnode::build_RNBNode(k,rndbvec[&k].clone(),v,r[&k].clone());
+/// # description
+/// builds a network of numeric node ids; see `build_RNetwork_ids`.
+pub fn build_RNetwork<A:Clone>(m: HashMap<usize,Vec<usize>>,rndbvec:HashMap<usize,rndb::RNDB<A>>,
+        r:HashMap<usize,f32>,c:f32) -> RNetwork<A> {
+    let m2 = m.into_iter().map(|(k,v)| (NodeId::from(k),v.into_iter().map(|x| NodeId::from(x)).collect())).collect();
+    let rndbvec2 = rndbvec.into_iter().map(|(k,v)| (NodeId::from(k),v)).collect();
+    let r2 = r.into_iter().map(|(k,v)| (NodeId::from(k),v)).collect();
+    build_RNetwork_ids(m2,rndbvec2,r2,c)
+}
+
+/// # description
+/// builds a network of external node ids; ids are registered in sorted
+/// order and each node's idn is its dense index. Records of `rndbvec`
+/// on other nodes are keyed by numeric external id and move to idns.
+pub fn build_RNetwork_ids<A:Clone>(m: HashMap<NodeId,Vec<NodeId>>,rndbvec:HashMap<NodeId,rndb::RNDB<A>>,
+        r:HashMap<NodeId,f32>,c:f32) -> RNetwork<A> {
+    let ids = id_registry::build_IdRegistry_sorted(m.keys().cloned().collect());
+    let mut nodes:Vec<rnode::RNBNode<A>> = Vec::new();
+    for (i,k) in ids.ids.iter().enumerate() {
+        let v:Vec<usize> = m[k].iter().map(|x| ids.idx(x).expect("neighbor is not a node")).collect();
+        let mut db = rndbvec[k].clone();
+        db.remap_nodes(&|x| Some(ids.idx(&NodeId::from(x)).expect("record of a node that is not a node")));
+        let rn = rnode::build_RNBNode(i,db,v,r[k].clone());
         nodes.push(rn);
     }
-    RNetwork{nodes:nodes,ans_box:ans::Ansbox{},c:c} 
+    RNetwork{nodes:nodes,ids:ids,ans_box:ans::Ansbox{},c:c} 
 }
 
-impl RNetwork {
+impl<A> RNetwork<A> {
 
     pub fn node_idn_to_index(&mut self,idn:usize) -> usize {
-        let l = self.nodes.len();
-        for i in 0..l {
-            if self.nodes[i].idn == idn {
-                return i;
+        assert!(idn < self.nodes.len());
+        idn
+    }
+
+    /// # description
+    /// adds node of external id `id` with edges to `neighbors` in both
+    /// directions. 
+    /// # return
+    /// idn of the new node
+    pub fn add_node(&mut self,id:NodeId,db:rndb::RNDB<A>,neighbors:Vec<NodeId>,resistance:f32) -> usize {
+        assert!(self.ids.idx(&id).is_none(), "node {} exists",id);
+        let v:Vec<usize> = neighbors.iter().map(|x| self.ids.idx(x).expect("neighbor is not a node")).collect();
+        let i = self.ids.register(id);
+        for x in v.iter() {
+            if !self.nodes[*x].neighbors.contains(&i) {
+                self.nodes[*x].neighbors.push(i);
             }
         }
+        self.nodes.push(rnode::build_RNBNode(i,db,v,resistance));
+        i
+    }
+
+    /// # description
+    /// removes node `idn` and its edges; nodes of greater idn move
+    /// down by one, and the records of each node follow.
+    pub fn remove_node(&mut self,idn:usize) -> rnode::RNBNode<A> {
+        let x = self.nodes.remove(idn);
+        self.ids.remove(idn);
+
+        let f = |i:usize| if i == idn {None} else if i > idn {Some(i - 1)} else {Some(i)};
+        for n in self.nodes.iter_mut() {
+            n.idn = f(n.idn).unwrap();
+            n.neighbors = n.neighbors.iter().filter_map(|y| f(*y)).collect();
+            n.db.remap_nodes(&f);
+        }
+        x
+    }
 
-        assert!(false);
-        1
+    /// idn of node with external id
+    pub fn external_to_idn(&self,id:&NodeId) -> Option<usize> {
+        self.ids.idx(id)
+    }
+
+    /// external id of node idn
+    pub fn idn_to_external(&self,idn:usize) -> &NodeId {
+        self.ids.id(idn)
     }
 
     /*
@@ -166,4 +227,95 @@ pub fn sample_resistancevec1() -> HashMap<usize,f32> {
 pub fn sample_RNBNetwork1() -> RNetwork {
     build_RNetwork(sample_node_idn_map1(),sample_rndb10(),
     sample_resistancevec1(),1.)
-}
\ No newline at end of file
+}
+
+/*
+for use with sample_QStruct_mixed1.
+*/ 
+pub fn sample_RNBNetwork_mixed1() -> RNetwork<AnsValue> {
+    let mut m: HashMap<usize,Vec<usize>> = HashMap::new();
+    m.insert(0,vec![1,2]);
+    m.insert(1,vec![0,3]);
+    m.insert(2,vec![0,3]);
+    m.insert(3,vec![1,2]);
+
+    let ka = vec![(0,Some(AnsValue::Int(6))),(1,Some(AnsValue::Real(0.8))),
+        (2,Some(AnsValue::Cat(0))),(3,Some(AnsValue::Vector(vec![1.,1.])))];
+
+    // node 0 no deception, node 1 all deception, node 2 does not know
+    // any answers, node 3 contradiction.
+    let mut rv: Vec<(usize,rndb::RNDB<AnsValue>)> = Vec::new();
+    for (i,o) in vec![(0,0),(1,1),(2,0),(3,2)].into_iter() {
+        let a = if i == 2 {ka.iter().map(|x| (x.0,None)).collect()} else {ka.clone()};
+        let obj:Vec<(usize,usize)> = (0..4).into_iter().map(|x| (x,o)).collect();
+        rv.push((i,rndb::build_RNDB(HashMap::from_iter(a),HashMap::from_iter(obj))));
+    }
+
+    let r:Vec<(usize,f32)> = (0..4).into_iter().map(|x| (x,20.)).collect();
+    build_RNetwork(m,HashMap::from_iter(rv),HashMap::from_iter(r),1.)
+}
+
+/*
+network of sample_RNBNetwork_mixed1 with named nodes.
+*/ 
+pub fn sample_RNBNetwork_named1() -> RNetwork<AnsValue> {
+    let n = vec!["dora","alma","cato","bex"];
+    let x = sample_RNBNetwork_mixed1();
+
+    let mut m: HashMap<NodeId,Vec<NodeId>> = HashMap::new();
+    let mut rv: HashMap<NodeId,rndb::RNDB<AnsValue>> = HashMap::new();
+    let mut r: HashMap<NodeId,f32> = HashMap::new();
+    for nd in x.nodes.into_iter() {
+        let k = NodeId::from(n[nd.idn]);
+        m.insert(k.clone(),nd.neighbors.iter().map(|y| NodeId::from(n[*y])).collect());
+        rv.insert(k.clone(),nd.db);
+        r.insert(k,nd.resistance);
+    }
+    build_RNetwork_ids(m,rv,r,1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__build_RNetwork_ids() {
+        let mut x = sample_RNBNetwork_named1();
+
+        // idn is the index of the sorted external id
+        let v:Vec<String> = x.ids.ids.iter().map(|y| y.to_string()).collect();
+        assert_eq!(v,vec!["alma","bex","cato","dora"]);
+        for i in 0..4 {
+            assert_eq!(x.nodes[i].idn,i);
+            assert_eq!(x.node_idn_to_index(i),i);
+        }
+
+        // dora (node 0 of mixed1) neighbors alma and cato
+        let d = x.external_to_idn(&"dora".into()).unwrap();
+        assert_eq!(d,3);
+        let mut ne = x.nodes[d].neighbors.clone();
+        ne.sort();
+        assert_eq!(ne,vec![0,2]);
+        assert_eq!(x.idn_to_external(2),&NodeId::from("cato"));
+        assert!(x.external_to_idn(&"eve".into()).is_none());
+
+        // numeric ids
+        let y = sample_RNBNetwork1();
+        for i in 0..11 {
+            assert_eq!(y.nodes[i].idn,i);
+            assert_eq!(y.idn_to_external(i),&NodeId::Num(i as u64));
+        }
+
+        // records on other nodes move from external ids to idns
+        let m = HashMap::from_iter(vec![(10,vec![30]),(20,vec![]),(30,vec![10])]);
+        let mut rv:HashMap<usize,rndb::RNDB> = (0..3).into_iter().map(|i| ((i + 1) * 10,rndb::build_RNDB(HashMap::new(),HashMap::new()))).collect();
+        let d = rv.get_mut(&10).unwrap();
+        d.sat_other.insert(30,HashMap::from_iter(vec![(0,0.5)]));
+        d.delegation_records.d1.insert(0,HashMap::from_iter(vec![(20,0.2)]));
+        let r = HashMap::from_iter(vec![(10,1.),(20,1.),(30,1.)]);
+        let z = build_RNetwork(m,rv,r,1.);
+        assert_eq!(z.nodes[0].neighbors,vec![2]);
+        assert_eq!(z.nodes[0].db.sat_other[&2][&0],0.5);
+        assert_eq!(z.nodes[0].db.delegation_records.d1[&0][&1],0.2);
+    }
+}
@@ -3,21 +3,52 @@ use crate::df;
 use crate::rndb;
 use crate::ans;
 use crate::f1pattern;
+use crate::std_rng;
+use crate::ans_domain::AnsDomain;
 use ndarray::{Array2,Dim};
 
 use std::collections::HashMap;
 use std::fmt;
 
+/// source of the answers that fill an F1 answer pattern
+#[derive(Clone,Debug)]
+pub enum F1Source {
+    /// answers wanted by Q
+    QDictated,
+    /// answers of node with no deception 
+    Honest,
+    /// answers of node by its objective 
+    Objective,
+    /// random values in answer domain
+    Random,
+    /// each answer is drawn from one of the sources at random
+    Mixed(Vec<F1Source>)
+}
+
 /// # description
-/// calculates the default F1 answer pattern
-pub fn default_F1_anspattern(r: &mut RNBNode,a: &mut ans::Ansbox,qrvec:Vec<(i32,i32)>) -> f1pattern::F1P {
+/// calculates the default F1 answer pattern: 6 answers to
+/// each question drawn from the node's objective.
+pub fn default_F1_anspattern<D:AnsDomain>(r: &mut RNBNode<D::Ans>,a: &mut ans::Ansbox,qrvec:Vec<D>) -> f1pattern::F1P<D::Ans> {
+    F1_anspattern(r,a,qrvec,Vec::new(),6,F1Source::Objective)
+}
+
+/// # description
+/// calculates an F1 answer pattern of `width` answers to each question. 
+/// 
+/// # arguments
+/// qrvec := answer domain of each question
+/// wanted := answer wanted by Q for each question; used by F1Source::QDictated
+/// width := number of answers to each question
+/// src := source of answers 
+pub fn F1_anspattern<D:AnsDomain>(r: &mut RNBNode<D::Ans>,a: &mut ans::Ansbox,qrvec:Vec<D>,
+    wanted:Vec<D::Ans>,width:usize,src:F1Source) -> f1pattern::F1P<D::Ans> {
+    assert!(width > 0);
 
-    // gather 6 answers to each question 
     let l = qrvec.len();
-    let mut x: Array2<i32> = Array2::zeros((l,6));
+    let mut x: Array2<D::Ans> = Array2::default((l,width));
     for i in 0..l {
-        for j in 0..6 {
-            let y = (*r).indep_ans_to_q(a,j,qrvec[i].clone());
+        for j in 0..width {
+            let y = (*r).F1_source_ans(a,i,qrvec[i].clone(),&wanted,&src);
             x[Dim((i,j))] = y;
         }
     }
@@ -28,27 +59,26 @@ pub fn default_F1_anspattern(r: &mut RNBNode,a: &mut ans::Ansbox,qrvec:Vec<(i32,
 
 /// the node class used for Respondent Network Bot. 
 #[derive(Clone)]
-pub struct RNBNode {
+pub struct RNBNode<A = i32> {
     // identifier
     pub idn: usize,
 
     // delegator function 
-    pub db: rndb::RNDB,
+    pub db: rndb::RNDB<A>,
     pub neighbors: Vec<usize>,
 
     // resistance value:
     // when resistance falls below 0,
     // struct instance will contradict its objective
-    pub resistance:f32,
-    pub f1: Option<f1pattern::F1P>
+    pub resistance:f32
 }
 
-pub fn build_RNBNode(idn:usize,db:rndb::RNDB,neighbors:Vec<usize>,resistance:f32) -> RNBNode {
+pub fn build_RNBNode<A>(idn:usize,db:rndb::RNDB<A>,neighbors:Vec<usize>,resistance:f32) -> RNBNode<A> {
     assert!(resistance > 0.);
-    RNBNode{idn:idn,db:db,neighbors:neighbors,resistance:resistance,f1:None}
+    RNBNode{idn:idn,db:db,neighbors:neighbors,resistance:resistance}
 }
 
-impl fmt::Display for RNBNode {
+impl<A> fmt::Display for RNBNode<A> {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut q = &format!("node {}\nneighbors {:?}\nresistance {}",self.idn,self.neighbors,self.resistance);
@@ -57,81 +87,89 @@ impl fmt::Display for RNBNode {
 }
 
 
-impl RNBNode {
+impl<A:Clone + PartialEq + Default + fmt::Debug> RNBNode<A> {
 
     /// # description
-    /// answers to q by one of the following:
-    /// 1. independent node answer.
-    /// 2. fix-F1 answer.
-    pub fn ans_to_q(&mut self,a: &mut ans::Ansbox,qi:usize,qr:(i32,i32)) -> i32 {
-        if self.f1.is_none() {
-            return self.indep_ans_to_q(a,qi,qr); 
-        }
-        let mut x = self.f1.clone().unwrap();
-        let y = x.next(qi);
-        self.f1 = Some(x);
-        y
+    /// answers to q; answers of an F1 fix are given by the fix registry
+    /// of `RNBENV`.
+    pub fn ans_to_q<D:AnsDomain<Ans=A>>(&mut self,a: &mut ans::Ansbox,qi:usize,qr:D) -> A {
+        self.indep_ans_to_q(a,qi,qr)
     }
 
     /// # description
     /// independent answer to q
-    pub fn indep_ans_to_q(&mut self,a: &mut ans::Ansbox,qi:usize,qr:(i32,i32)) -> i32 {
-        (*a).obj_ans(qr,self.db.ans[&qi].clone(),self.db.obj[&qi].clone())
+    pub fn indep_ans_to_q<D:AnsDomain<Ans=A>>(&mut self,a: &mut ans::Ansbox,qi:usize,qr:D) -> A {
+        (*a).obj_ans(&qr,self.db.ans[&qi].clone(),self.db.obj[&qi].clone())
+    }
+
+    /// # description
+    /// one answer to q drawn from source `src` 
+    pub fn F1_source_ans<D:AnsDomain<Ans=A>>(&mut self,a: &mut ans::Ansbox,qi:usize,qr:D,
+        wanted:&Vec<A>,src:&F1Source) -> A {
+        match src {
+            F1Source::QDictated => {
+                assert!(qi < wanted.len(), "no wanted answer for question {}",qi);
+                qr.nearest(&wanted[qi])
+            },
+            F1Source::Honest => (*a).obj_ans(&qr,self.db.ans[&qi].clone(),0),
+            F1Source::Objective => self.indep_ans_to_q(a,qi,qr),
+            F1Source::Random => qr.random(),
+            F1Source::Mixed(v) => {
+                assert!(v.len() > 0);
+                let i = std_rng::random_i32_in_range((0,v.len() as i32 - 1)) as usize;
+                self.F1_source_ans(a,qi,qr,wanted,&v[i])
+            }
+        }
     }
 
     /// # description
     /// instantiates a df::DPath used for node delegation for a question
     pub fn delegate(&mut self,qi:usize) {
+        self.start_delegation();
+        let mut db2 = self.db.clone();
+        self.delegate_one(&mut db2,qi);
+        self.db = db2;
+    }
+
+    /// # description
+    /// instantiates an empty df::DPath headed by node
+    pub fn start_delegation(&mut self) {
         let mut dp = df::DPath{sm:HashMap::new(),na:HashMap::new(),
             head:self.idn,next_ref:Vec::new(),dscore: None};
         dp.sm.insert(self.idn,Vec::new());
         self.db.delegation_path = Some(dp);
-        let mut db2 = self.db.clone();
-        self.delegate_one(&mut db2,qi);
-        self.db = db2;
     }
 
     /// # description
     /// fetch all neighbors that satisfy objective based on satisfaction
     /// rate map `sat_other`. 
-    pub fn delegate_one(&mut self,db: &mut rndb::RNDB,qi:usize) {
-        let mut dep = (*db).delegation_path.clone().unwrap();
-        dep.sm.insert(self.idn,Vec::new());
-        let neighs = self.neighbors.clone();        
+    pub fn delegate_one(&mut self,db: &mut rndb::RNDB<A>,qi:usize) {
+        let c = self.delegate_candidates(db);
 
-        for n in neighs.iter() {
-            let mut i:f32 = 0.;
+        // case: qualifying neighbors by satisfaction rate 
+        let v:Vec<usize> = c.into_iter().filter(|n| self.fetch_neighbor_qsat_rate(db,*n,qi) >= 0.5).collect();
+        self.delegate_to(db,v);
+    }
 
+    /// # description
+    /// neighbors that are not in the delegation path of `db` 
+    pub fn delegate_candidates(&self,db: &rndb::RNDB<A>) -> Vec<usize> {
+        let dep = (*db).delegation_path.as_ref().unwrap();
+        self.neighbors.iter().filter(|n| {
             // case: neighbor of node already in delegation path search map
-            if dep.sm.contains_key(n) {
-                continue; 
-            }
-
-            let mut stat:bool = false;
-            for x in dep.next_ref.iter() {
-                if *x == *n {
-                    stat = true;
-                    break;
-                }
-            }
-
-            // case: neighbor of node already a reference
-            if stat {
-                continue;
-            }
-
-            // fetch satisfaction rate 
-            let i = self.fetch_neighbor_qsat_rate(db,*n,qi);
+            // or already a reference
+            !dep.sm.contains_key(n) && !dep.next_ref.contains(n)
+        }).cloned().collect()
+    }
 
-            if i >= 0.5 {
-                // case: qualifying neighbors
-                let mut x = dep.sm.get_mut(&self.idn).unwrap();
-                x.push(*n);
+    /// # description
+    /// adds nodes `v` as delegates of node to the delegation path of `db`
+    pub fn delegate_to(&mut self,db: &mut rndb::RNDB<A>,v:Vec<usize>) {
+        let mut dep = (*db).delegation_path.clone().unwrap();
+        dep.sm.insert(self.idn,v.clone());
 
-                // add to next ref
-                dep.next_ref.push(*n);
-            }
-        }
+        // add to next ref
+        dep.next_ref.extend(v);
         (*db).delegation_path = Some(dep);
     }
 
@@ -140,7 +178,7 @@ impl RNBNode {
     /// 
     /// # NOTE
     /// misnomer for function: not just neighbors but any other node. 
-    pub fn fetch_neighbor_qsat_rate(&mut self,db: &mut rndb::RNDB,ni:usize,qi:usize) -> f32 {        
+    pub fn fetch_neighbor_qsat_rate(&mut self,db: &mut rndb::RNDB<A>,ni:usize,qi:usize) -> f32 {        
         let mut qsat:f32 = 0.;
 
         // case: neighbor is registered in sat other  
@@ -180,8 +218,9 @@ impl RNBNode {
     }
 
     /// # description 
-    /// processes node delegation; outputs the mean answer of the delegate nodes
-    pub fn process_delegation(&mut self,qi:usize,ans_range:(i32,i32),node_ans:i32) -> Option<i32> {
+    /// processes node delegation; outputs the mean answer of the node
+    /// and its delegate nodes
+    pub fn process_delegation<D:AnsDomain<Ans=A>>(&mut self,qi:usize,ans_range:D,node_ans:A) -> Option<A> {
         assert!(!self.db.delegation_path.is_none());
         let na = self.db.delegation_path.as_ref().unwrap().na.clone();
 
@@ -189,17 +228,15 @@ impl RNBNode {
             return None; 
         }
 
-        let mut a: i32 = 0;
-        let ard = ans_range.1 - ans_range.0;
+        // node's share of the mean holds the default answer
+        let mut a: Vec<(A,f32)> = vec![(A::default(),1.)];
         for (k,v) in na.into_iter() {
-            let dx = (node_ans - v).abs() as f32 / (ard as f32); 
+            let dx = ans_range.normalize(ans_range.distance(&node_ans,&v)); 
             self.mod_delegation_record(qi,k,dx);
-            a += v;
+            a.push((v,1.));
         }
 
-        let a_: f32 = (a as f32) / ((1 + 
-            self.db.delegation_path.as_ref().unwrap().na.len()) as f32);
-        Some(a_.round() as i32)
+        Some(ans_range.mean(&a))
     }
 
     pub fn mod_delegation_record(&mut self,qi:usize,ni:usize,s:f32) {
@@ -227,13 +264,13 @@ impl RNBNode {
 
     /// # description
     /// updates db sat_other map 
-    pub fn update_sat_map(&mut self,qi:usize,ans_range:(i32,i32),node_ans:i32,c:f32) -> Option<i32> {
+    pub fn update_sat_map<D:AnsDomain<Ans=A>>(&mut self,qi:usize,ans_range:D,node_ans:A,c:f32) -> Option<A> {
         let del = self.process_delegation(qi,ans_range,node_ans);
         let na = self.db.delegation_path.as_ref().unwrap().na.clone();
         for (k,v) in na.into_iter() {
             let s = 1. - self.db.delegation_records.d1[&qi][&k] * c * 
                     self.db.delegation_records.d2[&qi][&k] as f32;
-            self.db.sat_other.get_mut(&k).unwrap().insert(qi,s);
+            self.db.sat_other.entry(k).or_insert(HashMap::new()).insert(qi,s);
         }
         del
     }
@@ -245,4 +282,77 @@ impl RNBNode {
         let n:Vec<usize> = self.neighbors.clone().into_iter().filter(|x| *x != qi).collect();
         self.neighbors = n;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rnetwork;
+    use crate::q_struct;
+
+    fn sample_qrvec() -> Vec<(i32,i32)> {
+        q_struct::sample_QStruct1().qs.into_iter().map(|x| x.ans_range).collect()
+    }
+
+    #[test]
+    fn test__F1_anspattern__rows_in_range() {
+        let mut rn = rnetwork::sample_RNBNetwork1();
+        let qrvec = sample_qrvec();
+        let wanted = vec![50,0,10,-25,6];
+        let srcs = vec![F1Source::QDictated,F1Source::Honest,F1Source::Objective,
+            F1Source::Random,F1Source::Mixed(vec![F1Source::QDictated,F1Source::Random])];
+
+        for i in 0..rn.nodes.len() {
+            for s in srcs.iter() {
+                let f1 = F1_anspattern(&mut rn.nodes[i],&mut rn.ans_box,qrvec.clone(),
+                    wanted.clone(),4,s.clone());
+                assert_eq!(f1.data().dim(),(5,4));
+                for (qi,qr) in qrvec.iter().enumerate() {
+                    for x in f1.data().row(qi).iter() {
+                        assert!(ans::i32_in_range(*qr,*x),"{} not in {:?} for question {}",x,qr,qi);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test__RNBNode_process_delegation() {
+        let mut rn = rnetwork::sample_RNBNetwork1();
+        let n = &mut rn.nodes[0];
+        let na:HashMap<usize,i32> = HashMap::from_iter(vec![(1,40),(2,70)]);
+        n.db.delegation_path = Some(df::DPath{sm:HashMap::new(),na:na,head:0,next_ref:Vec::new(),dscore:None});
+
+        // sum of delegate answers over 1 + delegates; the node's answer 60
+        // is not in the mean
+        assert_eq!(n.process_delegation(0,(0,100),60),Some(37));
+        assert_eq!(n.db.delegation_records.d1[&0][&2],0.1);
+    }
+
+    #[test]
+    fn test__F1_anspattern__question_indexing() {
+        let mut rn = rnetwork::sample_RNBNetwork1();
+        let ni = rn.node_idn_to_index(0);
+        let qrvec = sample_qrvec();
+
+        // node 0 knows all answers
+        let mut f1 = F1_anspattern(&mut rn.nodes[ni],&mut rn.ans_box,qrvec.clone(),
+            Vec::new(),3,F1Source::Honest);
+        let ka = vec![50,40,10,-25,6];
+        for qi in 0..5 {
+            for x in f1.data().row(qi).iter() {
+                assert_eq!(*x,ka[qi]);
+            }
+            assert_eq!(f1.next(qi),ka[qi]);
+        }
+
+        // Q-dictated answers of width 1
+        let wanted = vec![0,-80,25,-100,11];
+        let mut f2 = F1_anspattern(&mut rn.nodes[ni],&mut rn.ans_box,qrvec,
+            wanted,1,F1Source::QDictated);
+        assert_eq!(f2.next(0),0);
+        assert_eq!(f2.next(1),-80);
+        assert_eq!(f2.next(2),25);
+        assert_eq!(f2.next(3),-100);
+        assert_eq!(f2.next(4),10);
+    }
+}
@@ -29,6 +29,15 @@ impl FloorDiv {
         (q3 as i32) * self.m
     }
 
+    /// `value` as an i64 product; None if it is out of i32 range
+    pub fn checked_value(&self,i:i32) -> Option<i32> {
+        let x: f64 = match self.t {
+            None => ((i as f64) / (self.b as f64)).floor(),
+            Some(t) => ((t as f64) / (self.b as f64)).trunc()
+        };
+        i32::try_from(x as i64 * self.m as i64).ok()
+    }
+
     pub fn clone(&mut self) -> FloorDiv {
         FloorDiv{t:self.t.clone(),b:self.b,m:self.m}
     }
@@ -108,8 +117,576 @@ impl IndexFractionNotation {
         x
     }
 
+    /// `output` with checked arithmetic; None if a div or the sum is out
+    /// of i32 range
+    pub fn checked_output(&self,i:i32) -> Option<i32> {
+        if self.divs.len() == 0 {
+            return Some(i);
+        }
+        self.divs.iter().try_fold(0i32,|x,q| x.checked_add(q.checked_value(i)?))
+    }
+
+}
+
+
+//////////////// serialization of IndexFractionNotation
+
+/// size of encoding of IndexFractionNotation compared to its raw sequence
+#[derive(Clone,Debug)]
+pub struct CompressionMetrics {
+    // length of sequence
+    pub n: usize,
+    // number of FloorDiv of encoding
+    pub n_divs: usize,
+    // bytes of sequence as i32 values
+    pub raw_bytes: usize,
+    // bytes of binary encoding 
+    pub encoded_bytes: usize,
+    // bytes of sequence as comma-separated text
+    pub raw_text_bytes: usize,
+    // bytes of text encoding 
+    pub encoded_text_bytes: usize
+}
+
+impl CompressionMetrics {
+
+    /// encoded bytes / raw bytes
+    pub fn ratio(&self) -> f32 {
+        if self.raw_bytes == 0 {
+            return 1.;
+        }
+        self.encoded_bytes as f32 / self.raw_bytes as f32
+    }
+
+    /// encoded text bytes / raw text bytes
+    pub fn text_ratio(&self) -> f32 {
+        if self.raw_text_bytes == 0 {
+            return 1.;
+        }
+        self.encoded_text_bytes as f32 / self.raw_text_bytes as f32
+    }
+}
+
+impl fmt::Display for CompressionMetrics {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "n {} divs {} | binary {}/{} ({:.3}) | text {}/{} ({:.3})",
+            self.n,self.n_divs,self.encoded_bytes,self.raw_bytes,self.ratio(),
+            self.encoded_text_bytes,self.raw_text_bytes,self.text_ratio())
+    }
+}
+
+/// longest sequence the decoders rebuild; a longer length in the input
+/// is an error, not an allocation
+pub const MAX_DECODED_LEN: usize = 1 << 20;
+
+/// writes i as a zigzag LEB128 varint
+pub fn write_varint(buf: &mut Vec<u8>,i:i64) {
+    let mut u: u64 = ((i << 1) ^ (i >> 63)) as u64;
+    loop {
+        let b = (u & 0x7f) as u8;
+        u >>= 7;
+        if u == 0 {
+            buf.push(b);
+            return;
+        }
+        buf.push(b | 0x80);
+    }
+}
+
+/// reads a zigzag LEB128 varint at position `*pos` of `buf`; advances `*pos`. 
+pub fn read_varint(buf: &[u8],pos: &mut usize) -> Result<i64,String> {
+    let mut u: u64 = 0;
+    let mut sh = 0;
+    loop {
+        if *pos >= buf.len() {
+            return Err("unexpected end of bytes".to_string());
+        }
+        if sh > 63 {
+            return Err("varint too long".to_string());
+        }
+        let b = buf[*pos];
+        *pos += 1;
+        u |= ((b & 0x7f) as u64) << sh;
+        if b & 0x80 == 0 {
+            break;
+        }
+        sh += 7;
+    }
+    Ok(((u >> 1) as i64) ^ -((u & 1) as i64))
+}
+
+fn varint_to_i32(i:i64) -> Result<i32,String> {
+    i32::try_from(i).map_err(|_| format!("value {} out of i32 range",i))
+}
+
+/// # description
+/// builds an IndexFractionNotation from its divs alone; the sequence `v`
+/// is rebuilt for indices 0..n.
+/// # return
+/// error if a value of the sequence overflows i32
+pub fn decode_index_fraction_notation(divs: Vec<FloorDiv>,n:usize) -> Result<IndexFractionNotation,String> {
+    let mut ifn = IndexFractionNotation{v:Array1::zeros(n),divs:divs};
+    let v:Option<Array1<i32>> = (0..n).into_iter().map(|i| ifn.checked_output(i as i32)).collect();
+    ifn.v = v.ok_or("value out of i32 range".to_string())?;
+    Ok(ifn)
+}
+
+/// # description
+/// decodes the binary format of `IndexFractionNotation::to_bytes`.
+pub fn index_fraction_notation_from_bytes(buf: &[u8]) -> Result<IndexFractionNotation,String> {
+    let mut pos: usize = 0;
+    let n = read_varint(buf,&mut pos)?;
+    let l = read_varint(buf,&mut pos)?;
+    if n < 0 || l < 0 {
+        return Err("negative length".to_string());
+    }
+    if n as u64 > MAX_DECODED_LEN as u64 {
+        return Err(format!("length {} above {}",n,MAX_DECODED_LEN));
+    }
+    // each div takes at least 2 bytes
+    if l as u64 > ((buf.len() - pos) / 2) as u64 {
+        return Err(format!("{} divs do not fit in {} bytes",l,buf.len() - pos));
+    }
+
+    let mut divs: Vec<FloorDiv> = Vec::new();
+    for _ in 0..l {
+        let tag = read_varint(buf,&mut pos)?;
+        if tag < 0 {
+            return Err(format!("invalid tag {}",tag));
+        }
+        let b = varint_to_i32(tag >> 1)?;
+        let t = if tag & 1 == 1 {Some(varint_to_i32(read_varint(buf,&mut pos)?)?)} else {None};
+        let m = varint_to_i32(read_varint(buf,&mut pos)?)?;
+        if b == 0 {
+            return Err("floor div with b = 0".to_string());
+        }
+        divs.push(FloorDiv{t:t,b:b,m:m});
+    }
+
+    if pos != buf.len() {
+        return Err(format!("{} trailing bytes",buf.len() - pos));
+    }
+    decode_index_fraction_notation(divs,n as usize)
+}
+
+/// # description
+/// decodes the text format of `IndexFractionNotation::to_text`.
+pub fn index_fraction_notation_from_text(s: &str) -> Result<IndexFractionNotation,String> {
+    let mut q = s.trim().split(';');
+    let h = q.next().unwrap();
+    let n = h.parse::<usize>().map_err(|_| format!("invalid length {}",h))?;
+    if n > MAX_DECODED_LEN {
+        return Err(format!("length {} above {}",n,MAX_DECODED_LEN));
+    }
+
+    let mut divs: Vec<FloorDiv> = Vec::new();
+    let r = q.next().unwrap_or("").trim();
+    if q.next().is_some() {
+        return Err("more than one ';'".to_string());
+    }
+
+    for d in r.split_whitespace() {
+        let x: Vec<&str> = d.split(',').collect();
+        if x.len() != 3 {
+            return Err(format!("invalid floor div {}",d));
+        }
+        let t = if x[0] == "_" {None} else {Some(x[0].parse::<i32>().map_err(|_| format!("invalid t {}",x[0]))?)};
+        let b = x[1].parse::<i32>().map_err(|_| format!("invalid b {}",x[1]))?;
+        let m = x[2].parse::<i32>().map_err(|_| format!("invalid m {}",x[2]))?;
+        if b == 0 {
+            return Err("floor div with b = 0".to_string());
+        }
+        divs.push(FloorDiv{t:t,b:b,m:m});
+    }
+    decode_index_fraction_notation(divs,n)
+}
+
+impl IndexFractionNotation {
+
+    /// # description
+    /// binary format, all values are zigzag varints:
+    /// [n][number of divs] then for each div [b << 1 | t is some][t if some][m]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::new();
+        write_varint(&mut buf,self.v.len() as i64);
+        write_varint(&mut buf,self.divs.len() as i64);
+        for d in self.divs.iter() {
+            let tag = ((d.b as i64) << 1) | if d.t.is_none() {0} else {1};
+            write_varint(&mut buf,tag);
+            if !d.t.is_none() {
+                write_varint(&mut buf,d.t.unwrap() as i64);
+            }
+            write_varint(&mut buf,d.m as i64);
+        }
+        buf
+    }
+
+    /// # description
+    /// text format: `n;t,b,m t,b,m ...` with t as `_` if none.
+    pub fn to_text(&self) -> String {
+        let d:Vec<String> = self.divs.iter().map(|d| format!("{},{},{}",
+            if d.t.is_none() {"_".to_string()} else {d.t.unwrap().to_string()},d.b,d.m)).collect();
+        format!("{};{}",self.v.len(),d.join(" "))
+    }
+
+    /// # description
+    /// outputs values of the encoding for the k indices after `v`.
+    pub fn extend(&mut self,k:usize) -> Array1<i32> {
+        let n = self.v.len();
+        (n..n + k).into_iter().map(|i| self.output(i as i32)).collect()
+    }
+
+    /// # description
+    /// compares size of encoding with size of raw sequence `v`.
+    pub fn compression_metrics(&self) -> CompressionMetrics {
+        let rt:Vec<String> = self.v.iter().map(|x| x.to_string()).collect();
+        CompressionMetrics{n:self.v.len(),n_divs:self.divs.len(),
+            raw_bytes:self.v.len() * std::mem::size_of::<i32>(),
+            encoded_bytes:self.to_bytes().len(),
+            raw_text_bytes:rt.join(",").len(),
+            encoded_text_bytes:self.to_text().len()}
+    }
+}
+
+//////////////// sequence encoders
+
+/// encoder of an Array1<i32> that can output the value at any index,
+/// including indices after the encoded sequence.
+pub trait SequenceEncoder {
+    /// encodes `v`; replaces any previous encoding
+    fn encode(&mut self,v:&Array1<i32>);
+    /// value at index i
+    fn decode_at(&mut self,i:usize) -> i32;
+    /// length of encoded sequence
+    fn len(&self) -> usize;
+    /// bytes of encoding, values counted as zigzag varints
+    fn encoded_size(&self) -> usize;
+    fn name(&self) -> String;
+
+    /// values of the encoding at indices 0..len
+    fn decode(&mut self) -> Array1<i32> {
+        (0..self.len()).into_iter().map(|i| self.decode_at(i)).collect()
+    }
+
+    /// values of the encoding for the n indices after the sequence
+    fn extrapolate(&mut self,n:usize) -> Array1<i32> {
+        let l = self.len();
+        (l..l + n).into_iter().map(|i| self.decode_at(i)).collect()
+    }
+}
+
+fn varint_size(i:i64) -> usize {
+    let mut buf: Vec<u8> = Vec::new();
+    write_varint(&mut buf,i);
+    buf.len()
+}
+
+impl SequenceEncoder for IndexFractionNotation {
+
+    fn encode(&mut self,v:&Array1<i32>) {
+        self.v = v.clone();
+        self.divs = Vec::new();
+        self.process();
+    }
+
+    fn decode_at(&mut self,i:usize) -> i32 {
+        // case: empty encoding; `output` would give the index
+        if self.divs.len() == 0 {
+            return 0;
+        }
+        self.output(i as i32)
+    }
+
+    fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    fn encoded_size(&self) -> usize {
+        self.to_bytes().len()
+    }
+
+    fn name(&self) -> String {
+        "index fraction".to_string()
+    }
+}
+
+/// run-length encoder; extrapolates by repeating the last value, or 0
+/// if empty
+pub struct RunLengthEncoder {
+    // (value,run length)
+    pub runs: Vec<(i32,usize)>,
+    pub n: usize
+}
+
+pub fn build_run_length_encoder() -> RunLengthEncoder {
+    RunLengthEncoder{runs:Vec::new(),n:0}
+}
+
+impl SequenceEncoder for RunLengthEncoder {
+
+    fn encode(&mut self,v:&Array1<i32>) {
+        self.runs = Vec::new();
+        self.n = v.len();
+        for x in v.iter() {
+            let l = self.runs.len();
+            if l > 0 && self.runs[l - 1].0 == *x {
+                self.runs[l - 1].1 += 1;
+            } else {
+                self.runs.push((*x,1));
+            }
+        }
+    }
+
+    fn decode_at(&mut self,i:usize) -> i32 {
+        if self.runs.len() == 0 {
+            return 0;
+        }
+        let mut j = 0;
+        for r in self.runs.iter() {
+            j += r.1;
+            if i < j {
+                return r.0;
+            }
+        }
+        self.runs[self.runs.len() - 1].0
+    }
+
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    fn encoded_size(&self) -> usize {
+        varint_size(self.runs.len() as i64) + self.runs.iter().map(|r|
+            varint_size(r.0 as i64) + varint_size(r.1 as i64)).sum::<usize>()
+    }
+
+    fn name(&self) -> String {
+        "run length".to_string()
+    }
+}
+
+/// delta encoder; stores first value and the differences of consecutive
+/// values. Extrapolates by repeating the last difference, or 0 if empty.
+pub struct DeltaEncoder {
+    pub first: i32,
+    pub deltas: Vec<i32>,
+    pub n: usize
+}
+
+pub fn build_delta_encoder() -> DeltaEncoder {
+    DeltaEncoder{first:0,deltas:Vec::new(),n:0}
+}
+
+impl SequenceEncoder for DeltaEncoder {
+
+    fn encode(&mut self,v:&Array1<i32>) {
+        self.n = v.len();
+        self.first = if self.n > 0 {v[0]} else {0};
+        // wrapping differences; decoding wraps back to the values
+        self.deltas = (1..self.n).into_iter().map(|i| v[i].wrapping_sub(v[i - 1])).collect();
+    }
+
+    fn decode_at(&mut self,i:usize) -> i32 {
+        if self.n == 0 {
+            return 0;
+        }
+        let l = self.deltas.len();
+        let j = if i < l {i} else {l};
+        let x = self.deltas[..j].iter().fold(self.first,|x,d| x.wrapping_add(*d));
+        if i > l && l > 0 {
+            return x.wrapping_add(((i - l) as i32).wrapping_mul(self.deltas[l - 1]));
+        }
+        x
+    }
+
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    fn encoded_size(&self) -> usize {
+        varint_size(self.n as i64) + varint_size(self.first as i64) + 
+            self.deltas.iter().map(|d| varint_size(*d as i64)).sum::<usize>()
+    }
+
+    fn name(&self) -> String {
+        "delta".to_string()
+    }
+}
+
+/// polynomial encoder; least-squares polynomial of degree `d` over index
+/// plus the residual of each value, so that decoding is exact. Extrapolates
+/// by the polynomial. Each coefficient is counted as 8 bytes.
+pub struct PolynomialEncoder {
+    pub d: usize,
+    // coefficients, lowest degree first
+    pub coeff: Vec<f64>,
+    pub residuals: Vec<i32>
+}
+
+pub fn build_polynomial_encoder(d:usize) -> PolynomialEncoder {
+    PolynomialEncoder{d:d,coeff:Vec::new(),residuals:Vec::new()}
+}
+
+/// # description
+/// solves the linear system ax = b by gaussian elimination with
+/// partial pivoting; singular columns are solved as 0.
+pub fn solve_linear_system(mut a:Vec<Vec<f64>>,mut b:Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for c in 0..n {
+        let p = (c..n).fold(c,|x,x2| if a[x2][c].abs() > a[x][c].abs() {x2} else {x});
+        a.swap(c,p);
+        b.swap(c,p);
+        if a[c][c].abs() < 1e-12 {
+            continue;
+        }
+
+        for r in c + 1..n {
+            let f = a[r][c] / a[c][c];
+            for k in c..n {
+                a[r][k] -= f * a[c][k];
+            }
+            b[r] -= f * b[c];
+        }
+    }
+
+    let mut x = vec![0.;n];
+    for c in (0..n).rev() {
+        if a[c][c].abs() < 1e-12 {
+            continue;
+        }
+        let s: f64 = (c + 1..n).into_iter().map(|k| a[c][k] * x[k]).sum();
+        x[c] = (b[c] - s) / a[c][c];
+    }
+    x
 }
 
+impl PolynomialEncoder {
+
+    pub fn poly(&self,i:usize) -> i32 {
+        let x = i as f64;
+        let mut y: f64 = 0.;
+        for c in self.coeff.iter().rev() {
+            y = y * x + c;
+        }
+        y.round() as i32
+    }
+}
+
+impl SequenceEncoder for PolynomialEncoder {
+
+    fn encode(&mut self,v:&Array1<i32>) {
+        let n = v.len();
+        // degree can not exceed n - 1
+        let d = if n == 0 {0} else {self.d.min(n - 1)};
+
+        // normal equations
+        let mut a = vec![vec![0.;d + 1];d + 1];
+        let mut b = vec![0.;d + 1];
+        for i in 0..n {
+            let p:Vec<f64> = (0..2 * d + 1).into_iter().map(|k| (i as f64).powi(k as i32)).collect();
+            for r in 0..d + 1 {
+                for c in 0..d + 1 {
+                    a[r][c] += p[r + c];
+                }
+                b[r] += p[r] * v[i] as f64;
+            }
+        }
+
+        self.coeff = solve_linear_system(a,b);
+        self.residuals = (0..n).into_iter().map(|i| v[i] - self.poly(i)).collect();
+    }
+
+    fn decode_at(&mut self,i:usize) -> i32 {
+        if i < self.residuals.len() {
+            return self.poly(i) + self.residuals[i];
+        }
+        self.poly(i)
+    }
+
+    fn len(&self) -> usize {
+        self.residuals.len()
+    }
+
+    fn encoded_size(&self) -> usize {
+        varint_size(self.residuals.len() as i64) + 8 * self.coeff.len() +
+            self.residuals.iter().map(|r| varint_size(*r as i64)).sum::<usize>()
+    }
+
+    fn name(&self) -> String {
+        format!("polynomial {}",self.d)
+    }
+}
+
+/// shortest-period encoder; stores the shortest block that repeats over the
+/// sequence. Extrapolates by continuing the repeat, or 0 if empty.
+pub struct PeriodEncoder {
+    pub block: Vec<i32>,
+    pub n: usize
+}
+
+pub fn build_period_encoder() -> PeriodEncoder {
+    PeriodEncoder{block:Vec::new(),n:0}
+}
+
+impl SequenceEncoder for PeriodEncoder {
+
+    fn encode(&mut self,v:&Array1<i32>) {
+        self.n = v.len();
+        let mut p = self.n;
+        for p2 in 1..self.n {
+            if (p2..self.n).into_iter().all(|i| v[i] == v[i - p2]) {
+                p = p2;
+                break;
+            }
+        }
+        self.block = v.iter().take(p).cloned().collect();
+    }
+
+    fn decode_at(&mut self,i:usize) -> i32 {
+        if self.block.len() == 0 {
+            return 0;
+        }
+        self.block[i % self.block.len()]
+    }
+
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    fn encoded_size(&self) -> usize {
+        varint_size(self.n as i64) + varint_size(self.block.len() as i64) +
+            self.block.iter().map(|x| varint_size(*x as i64)).sum::<usize>()
+    }
+
+    fn name(&self) -> String {
+        "period".to_string()
+    }
+}
+
+/// all encoders, each encoding `v`
+pub fn encode_all(v:&Array1<i32>) -> Vec<Box<dyn SequenceEncoder>> {
+    let mut es: Vec<Box<dyn SequenceEncoder>> = vec![
+        Box::new(build_index_fraction_notation(Array1::zeros(0))),
+        Box::new(build_run_length_encoder()),
+        Box::new(build_delta_encoder()),
+        Box::new(build_polynomial_encoder(1)),
+        Box::new(build_polynomial_encoder(2)),
+        Box::new(build_period_encoder())];
+    for e in es.iter_mut() {
+        e.encode(v);
+    }
+    es
+}
+
+/// # description
+/// encodes `v` by every encoder and outputs the encoder with the smallest
+/// encoded size; ties go to the first encoder in `encode_all`.
+pub fn smallest_encoding(v:&Array1<i32>) -> Box<dyn SequenceEncoder> {
+    let es = encode_all(v);
+    es.into_iter().fold(None,|x: Option<Box<dyn SequenceEncoder>>,x2|
+        if x.is_none() || x2.encoded_size() < x.as_ref().unwrap().encoded_size() {Some(x2)} else {x}).unwrap()
+}
 
 #[cfg(test)]
 mod tests {
@@ -166,4 +743,150 @@ mod tests {
             assert_eq!(j,g);
         }
     }
+
+    #[test]
+    fn test_IndexFractionNotation_serialization() {
+        let qs = vec![arr1(&[0,5,6,8,11,14]),arr1(&[-3,14,7,10,18]),
+            arr1(&[5,5,5,5,5]),arr1(&[-70000,123456,0,-1])];
+
+        for q in qs.into_iter() {
+            let mut ifn = build_index_fraction_notation(q.clone());
+            ifn.process();
+
+            // binary 
+            let b = ifn.to_bytes();
+            let mut ifn2 = index_fraction_notation_from_bytes(&b).unwrap();
+            assert_eq!(ifn2.v,q);
+            assert_eq!(ifn2.divs.len(),ifn.divs.len());
+
+            // text
+            let t = ifn.to_text();
+            let mut ifn3 = index_fraction_notation_from_text(&t).unwrap();
+            assert_eq!(ifn3.v,q);
+
+            // extension
+            assert_eq!(ifn2.extend(3),ifn.extend(3));
+            assert_eq!(ifn3.extend(3),ifn.extend(3));
+        }
+
+        let mut ifn4 = index_fraction_notation_from_text("5;14,1,1 _,1,3").unwrap();
+        assert_eq!(ifn4.v,arr1(&[14,17,20,23,26]));
+        assert_eq!(ifn4.extend(2),arr1(&[29,32]));
+
+        assert!(index_fraction_notation_from_text("5;14,1").is_err());
+        assert!(index_fraction_notation_from_text("x;").is_err());
+        assert!(index_fraction_notation_from_text("3;_,0,1").is_err());
+        assert!(index_fraction_notation_from_bytes(&[4,2,2]).is_err());
+
+        // corrupt lengths are errors, not allocations
+        let mut b = Vec::new();
+        write_varint(&mut b,i64::MAX);
+        write_varint(&mut b,0);
+        assert!(index_fraction_notation_from_bytes(&b).is_err());
+        let mut b2 = Vec::new();
+        write_varint(&mut b2,5);
+        write_varint(&mut b2,1 << 40);
+        assert!(index_fraction_notation_from_bytes(&b2).is_err());
+        assert!(index_fraction_notation_from_text("18446744073709551615;").is_err());
+
+        // values that overflow i32 are errors
+        assert!(index_fraction_notation_from_text("3;_,1,2147483647 _,1,2147483647").is_err());
+        assert!(index_fraction_notation_from_text("3;_,1,2147483647").is_err());
+        let x = index_fraction_notation_from_text("3;_,1,1073741823").unwrap();
+        assert_eq!(x.v,arr1(&[0,1073741823,2147483646]));
+        assert_eq!(index_fraction_notation_from_bytes(&x.to_bytes()).unwrap().v,x.v);
+        let mut b3 = Vec::new();
+        write_varint(&mut b3,3);
+        write_varint(&mut b3,2);
+        for _ in 0..2 {
+            write_varint(&mut b3,1 << 1);
+            write_varint(&mut b3,i32::MAX as i64);
+        }
+        assert!(index_fraction_notation_from_bytes(&b3).is_err());
+    }
+
+    #[test]
+    fn test_IndexFractionNotation_compression_metrics() {
+        let q = Array1::from_vec(vec![7;100]);
+        let mut ifn = build_index_fraction_notation(q);
+        ifn.process();
+        let m = ifn.compression_metrics();
+        assert_eq!(m.n,100);
+        assert_eq!(m.n_divs,1);
+        assert_eq!(m.raw_bytes,400);
+        assert_eq!(m.encoded_bytes,6);
+        assert!(m.ratio() < 0.05);
+        assert!(m.text_ratio() < 0.05);
+    }
+
+    #[test]
+    fn test_SequenceEncoder_decode() {
+        let qs = vec![arr1(&[0,5,6,8,11,14]),arr1(&[-3,14,7,10,18]),
+            arr1(&[5,5,5,5,5]),arr1(&[4,4,1,1,1,9]),arr1(&[7])];
+
+        for q in qs.into_iter() {
+            for mut e in encode_all(&q).into_iter() {
+                assert_eq!(e.len(),q.len());
+                assert_eq!(e.decode(),q,"encoder {}",e.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_SequenceEncoder_extrapolate() {
+        let mut e1 = build_run_length_encoder();
+        e1.encode(&arr1(&[4,4,1,1,1,9]));
+        assert_eq!(e1.runs,vec![(4,2),(1,3),(9,1)]);
+        assert_eq!(e1.extrapolate(2),arr1(&[9,9]));
+
+        let mut e2 = build_delta_encoder();
+        e2.encode(&arr1(&[3,5,4,7,10]));
+        assert_eq!(e2.extrapolate(3),arr1(&[13,16,19]));
+
+        let mut e3 = build_polynomial_encoder(2);
+        e3.encode(&arr1(&[1,2,5,10,17,26]));
+        assert_eq!(e3.residuals,vec![0;6]);
+        assert_eq!(e3.extrapolate(2),arr1(&[37,50]));
+
+        let mut e4 = build_period_encoder();
+        e4.encode(&arr1(&[2,-1,6,2,-1,6,2]));
+        assert_eq!(e4.block,vec![2,-1,6]);
+        assert_eq!(e4.extrapolate(4),arr1(&[-1,6,2,-1]));
+    }
+
+    #[test]
+    fn test_SequenceEncoder_empty() {
+        let q:Array1<i32> = Array1::zeros(0);
+        for mut e in encode_all(&q).into_iter() {
+            assert_eq!(e.len(),0);
+            assert_eq!(e.decode(),q,"encoder {}",e.name());
+            assert_eq!(e.extrapolate(3),arr1(&[0,0,0]),"encoder {}",e.name());
+        }
+    }
+
+    #[test]
+    fn test_DeltaEncoder_overflow() {
+        let q = arr1(&[i32::MIN,i32::MAX,i32::MIN,-1]);
+        let mut e = build_delta_encoder();
+        e.encode(&q);
+        assert_eq!(e.deltas,vec![-1,1,i32::MAX]);
+        assert_eq!(e.decode(),q);
+        assert_eq!(e.extrapolate(2),arr1(&[i32::MAX - 1,-3]));
+    }
+
+    #[test]
+    fn test_smallest_encoding() {
+        let v:Array1<i32> = (0..60).into_iter().map(|i| [3,9,-4][i % 3]).collect();
+        let mut e = smallest_encoding(&v);
+        assert_eq!(e.name(),"period");
+        assert_eq!(e.decode(),v);
+
+        let v2:Array1<i32> = (0..60).into_iter().map(|i| 3 * (i * i) as i32 - 1000).collect();
+        let e2 = smallest_encoding(&v2);
+        assert_eq!(e2.name(),"polynomial 2");
+
+        let v3:Array1<i32> = (0..60).into_iter().map(|i| if i < 30 {100} else {-100}).collect();
+        let e3 = smallest_encoding(&v3);
+        assert_eq!(e3.name(),"run length");
+    }
 }
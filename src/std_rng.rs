@@ -1,17 +1,41 @@
-
 use rand::prelude::*;
+use std::cell::RefCell;
 
 /*
 std random functions
 */
 
+thread_local! {
+    // generator of the random functions of a thread
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// reseeds the random functions of the current thread
+pub fn set_seed(seed:u64) {
+    RNG.with(|r| *r.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// state of the random functions of the current thread
+pub fn save_state() -> StdRng {
+    RNG.with(|r| r.borrow().clone())
+}
+
+/// restores a state from `save_state`
+pub fn restore_state(s:StdRng) {
+    RNG.with(|r| *r.borrow_mut() = s);
+}
+
+fn random_f32() -> f32 {
+    RNG.with(|r| r.borrow_mut().gen())
+}
+
 pub fn random_f32_in_range(r:(f32,f32)) -> f32 {
-    let q: f32 = random();
+    let q: f32 = random_f32();
     r.0 + (r.1 - r.0) * q
 }
 
 pub fn random_i32_in_range(r:(i32,i32)) -> i32 {
-    let q: f32 = random();
+    let q: f32 = random_f32();
     (r.0 as f32 + q * (r.1 - r.0) as f32).round() as i32
 }
 
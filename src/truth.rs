@@ -0,0 +1,272 @@
+//! truth-discovery estimators.
+//! Q uses these to estimate the answer to a question it does not
+//! know from the mean answers of nodes in QData.
+use crate::q_struct;
+use crate::rdata;
+use crate::ans_domain::AnsDomain;
+use ndarray::{Dim,s};
+
+/// estimator of the answer to a question without a known answer
+#[derive(Clone,Debug)]
+pub enum TruthEstimator {
+    /// y-weighted mean of node mean answers
+    YWeightedMean,
+    /// y-weighted median of node mean answers; the node mean answer
+    /// with the minimum weighted distance to all others
+    WeightedMedian,
+    /// mean of node mean answers by node weights, weights are from past
+    /// contradiction and distance to the estimate; repeated `iterations`
+    /// times or until the estimate does not change.
+    ReweightedConsensus{iterations:usize},
+    /// EM over node reliability and the answer of each unknown question;
+    /// a node answer agrees with an answer if their normalized distance
+    /// is at most `tolerance`.
+    DawidSkene{iterations:usize,tolerance:f32}
+}
+
+/// estimated answer and its confidence in [0,1]
+#[derive(Clone,Debug,PartialEq)]
+pub struct TruthEstimate<A> {
+    pub ans: A,
+    pub confidence: f32
+}
+
+/// (node,node mean answer,y) of every node that answered question `qi`
+pub fn node_answers<A:Clone>(rd:&rdata::QData<A>,qi:usize) -> Vec<(usize,A,f32)> {
+    let r = rd.y.dim().0;
+    (0..r).into_iter().filter(|i| rd.y[Dim((*i,qi))] > 0)
+        .map(|i| (i,rd.z[Dim((i,qi))].clone(),rd.y[Dim((i,qi))] as f32)).collect()
+}
+
+/// # description
+/// confidence of estimate `a`: 1 - weighted mean normalized distance of the
+/// answers `v` to `a`. No answers is confidence 0.
+pub fn agreement<D:AnsDomain>(dom:&D,a:&D::Ans,v:&Vec<(D::Ans,f32)>) -> f32 {
+    let s:f32 = v.iter().map(|x| x.1).sum();
+    if s == 0. {
+        return 0.;
+    }
+    let d:f32 = v.iter().map(|x| x.1 * dom.normalize(dom.distance(a,&x.0))).sum::<f32>() / s;
+    (1. - d).max(0.)
+}
+
+/// # description
+/// estimates the answer to question `qi` of `qs` by estimator `est`.
+pub fn estimate_truth<D:AnsDomain>(est:&TruthEstimator,qs:&Vec<q_struct::Q<D>>,rd:&rdata::QData<D::Ans>,qi:usize) -> TruthEstimate<D::Ans> {
+    let dom = &qs[qi].ans_range;
+    let v:Vec<(D::Ans,f32)> = node_answers(rd,qi).into_iter().map(|x| (x.1,x.2)).collect();
+
+    // case: no node answered, use midpoint
+    if v.len() == 0 {
+        return TruthEstimate{ans:dom.midpoint(),confidence:0.};
+    }
+
+    match est {
+        TruthEstimator::YWeightedMean => {
+            let a = dom.mean(&v);
+            let c = agreement(dom,&a,&v);
+            TruthEstimate{ans:a,confidence:c}
+        },
+        TruthEstimator::WeightedMedian => weighted_median(dom,&v),
+        TruthEstimator::ReweightedConsensus{iterations} => reweighted_consensus(dom,rd,qi,*iterations),
+        TruthEstimator::DawidSkene{iterations,tolerance} => dawid_skene(qs,rd,qi,*iterations,*tolerance)
+    }
+}
+
+/// # description
+/// weighted medoid of answers `v`; for an integer or real domain this
+/// is the weighted median.
+pub fn weighted_median<D:AnsDomain>(dom:&D,v:&Vec<(D::Ans,f32)>) -> TruthEstimate<D::Ans> {
+    let (i,_) = v.iter().enumerate().map(|(i,x)| (i,v.iter().map(|x2| x2.1 * dom.distance(&x.0,&x2.0)).sum::<f32>()))
+        .fold((0,f32::MAX),|x,x2| if x2.1 < x.1 {x2} else {x});
+    let a = v[i].0.clone();
+    let c = agreement(dom,&a,v);
+    TruthEstimate{ans:a,confidence:c}
+}
+
+/// # description
+/// prior weight of node `ni` from past contradiction: 1 - mean contradiction
+/// of node over all questions it answered, excluding `qi`.
+pub fn contradiction_prior<A>(rd:&rdata::QData<A>,ni:usize,qi:usize) -> f32 {
+    let x = rd.x.slice(s![ni,..]);
+    let y = rd.y.slice(s![ni,..]);
+    let v:Vec<f32> = (0..x.len()).into_iter().filter(|j| *j != qi && y[*j] > 0).map(|j| x[j]).collect();
+    if v.len() == 0 {
+        return 1.;
+    }
+    (1. - v.iter().sum::<f32>() / v.len() as f32).max(0.)
+}
+
+/// # description
+/// iteratively reweighted consensus. Node i has weight
+///     y_i * prior_i * (1 - normalized distance of z_i to estimate),
+/// with prior_i from `contradiction_prior`; the estimate is the weighted
+/// mean, starting from the y-weighted mean.
+pub fn reweighted_consensus<D:AnsDomain>(dom:&D,rd:&rdata::QData<D::Ans>,qi:usize,iterations:usize) -> TruthEstimate<D::Ans> {
+    let na = node_answers(rd,qi);
+    let pv:Vec<f32> = na.iter().map(|x| contradiction_prior(rd,x.0,qi)).collect();
+    let v:Vec<(D::Ans,f32)> = na.iter().map(|x| (x.1.clone(),x.2)).collect();
+
+    let mut a = dom.mean(&v);
+    for _ in 0..iterations {
+        let v2:Vec<(D::Ans,f32)> = na.iter().enumerate().map(|(i,x)| (x.1.clone(),
+            x.2 * pv[i] * (1. - dom.normalize(dom.distance(&a,&x.1))).max(0.))).collect();
+        let a2 = dom.mean(&v2);
+        if a2 == a {
+            break;
+        }
+        a = a2;
+    }
+
+    // confidence is agreement under the prior weights
+    let v3:Vec<(D::Ans,f32)> = na.iter().enumerate().map(|(i,x)| (x.1.clone(),x.2 * pv[i])).collect();
+    let c = agreement(dom,&a,&v3);
+    TruthEstimate{ans:a,confidence:c}
+}
+
+/// # description
+/// Dawid-Skene style EM with one reliability p_i per node. The answer to an
+/// unknown question is one of the node mean answers (candidates); a node
+/// with reliability p_i gives an answer that agrees with the true answer
+/// with probability p_i. Questions with a known answer fix their truth.
+/// (E) posterior of each candidate of each unknown question.
+/// (M) p_i = expected fraction of agreeing answers of node i.
+/// Each node mean answer counts once, whatever its y.
+/// Outputs the candidate of max posterior for `qi`; confidence is that posterior.
+pub fn dawid_skene<D:AnsDomain>(qs:&Vec<q_struct::Q<D>>,rd:&rdata::QData<D::Ans>,qi:usize,
+    iterations:usize,tolerance:f32) -> TruthEstimate<D::Ans> {
+    let (r,c) = rd.y.dim();
+    let agrees = |j:usize,a:&D::Ans,b:&D::Ans| qs[j].ans_range.normalize(qs[j].ans_range.distance(a,b)) <= tolerance;
+
+    // answers and candidates of each question
+    let na:Vec<Vec<(usize,D::Ans,f32)>> = (0..c).into_iter().map(|j| node_answers(rd,j)).collect();
+    let cand:Vec<Vec<D::Ans>> = (0..c).into_iter().map(|j| {
+        if !qs[j].qa.is_none() {
+            return vec![qs[j].qa.clone().unwrap()];
+        }
+        let mut v:Vec<D::Ans> = Vec::new();
+        for x in na[j].iter() {
+            if !v.contains(&x.1) {
+                v.push(x.1.clone());
+            }
+        }
+        v
+    }).collect();
+
+    // agreement of each answer of each question with each candidate
+    let ag:Vec<Vec<Vec<bool>>> = (0..c).into_iter().map(|j| na[j].iter().map(|x|
+        cand[j].iter().map(|a| agrees(j,&x.1,a)).collect()).collect()).collect();
+
+    // initial posteriors: by votes
+    let mut post:Vec<Vec<f32>> = (0..c).into_iter().map(|j| {
+        let mut p:Vec<f32> = (0..cand[j].len()).into_iter().map(|k|
+            na[j].iter().enumerate().filter(|(i,_)| ag[j][*i][k]).count() as f32).collect();
+        let s:f32 = p.iter().sum();
+        if s > 0. {
+            p = p.into_iter().map(|x| x / s).collect();
+        }
+        p
+    }).collect();
+
+    let mut rel:Vec<f32> = vec![0.5;r];
+    for _ in 0..iterations {
+        // M-step: node reliability, smoothed
+        let mut num:Vec<f32> = vec![1.;r];
+        let mut den:Vec<f32> = vec![2.;r];
+        for j in 0..c {
+            for (i,x) in na[j].iter().enumerate() {
+                let e:f32 = (0..cand[j].len()).into_iter().filter(|k| ag[j][i][*k]).map(|k| post[j][k]).sum();
+                num[x.0] += e;
+                den[x.0] += 1.;
+            }
+        }
+        rel = (0..r).into_iter().map(|i| (num[i] / den[i]).clamp(0.01,0.99)).collect();
+
+        // E-step: posteriors of candidates of unknown questions
+        for j in 0..c {
+            if !qs[j].qa.is_none() || cand[j].len() == 0 {
+                continue;
+            }
+            let l:Vec<f32> = (0..cand[j].len()).into_iter().map(|k| na[j].iter().enumerate().map(|(i,x)| {
+                let p = if ag[j][i][k] {rel[x.0]} else {1. - rel[x.0]};
+                p.ln()}).sum::<f32>()).collect();
+            let m = l.iter().cloned().fold(f32::MIN,f32::max);
+            let e:Vec<f32> = l.into_iter().map(|x| (x - m).exp()).collect();
+            let s:f32 = e.iter().sum();
+            post[j] = e.into_iter().map(|x| x / s).collect();
+        }
+    }
+
+    let (k,p) = post[qi].iter().cloned().enumerate().fold((0,f32::MIN),|x,x2| if x2.1 > x.1 {x2} else {x});
+    TruthEstimate{ans:cand[qi][k].clone(),confidence:p}
+}
+
+/// QStruct with one unknown question 1 in (-80,80) and
+/// known question 0 in (0,100) with answer 50;
+/// nodes 0,1,2 answer 40 to question 1 and 50 to question 0;
+/// nodes 3,4 answer -75 to question 1 more often and 0 to question 0.
+pub fn truth_estimator__test_case1() -> q_struct::QStruct {
+    let q0 = q_struct::Q{qa:Some(50),ans_range:(0,100)};
+    let q1 = q_struct::Q{qa:None,ans_range:(-80,80)};
+    let mut q = q_struct::build_QStruct(vec![q0,q1],5,100);
+
+    for i in 0..3 {
+        q.rd.log_node_response(i,vec![i].into_iter().collect(),0,(0,100),Some(50),50);
+        q.rd.log_node_response(i,vec![i].into_iter().collect(),1,(-80,80),Some(40),40);
+    }
+
+    for i in 3..5 {
+        q.rd.log_node_response(i,vec![i].into_iter().collect(),0,(0,100),Some(50),0);
+        for _ in 0..4 {
+            q.rd.log_node_response(i,vec![i].into_iter().collect(),1,(-80,80),Some(40),-75);
+        }
+    }
+    q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test__estimate_truth() {
+        let q = truth_estimator__test_case1();
+
+        // y-weighted mean is skewed by the deceptive nodes
+        let e0 = estimate_truth(&TruthEstimator::YWeightedMean,&q.qs,&q.rd,1);
+        assert_eq!(e0.ans,-44);
+
+        let e1 = estimate_truth(&TruthEstimator::WeightedMedian,&q.qs,&q.rd,1);
+        assert_eq!(e1.ans,-75);
+
+        let e2 = estimate_truth(&TruthEstimator::ReweightedConsensus{iterations:20},&q.qs,&q.rd,1);
+        assert_eq!(e2.ans,40);
+        assert!(e2.confidence > e0.confidence);
+
+        let e3 = estimate_truth(&TruthEstimator::DawidSkene{iterations:20,tolerance:0.05},&q.qs,&q.rd,1);
+        assert_eq!(e3.ans,40);
+        assert!(e3.confidence > 0.9);
+
+        // known question and unanswered question
+        let e4 = estimate_truth(&TruthEstimator::WeightedMedian,&q.qs,&q.rd,0);
+        assert_eq!(e4.ans,50);
+        let q2 = q_struct::sample_QStruct1();
+        let e5 = estimate_truth(&TruthEstimator::DawidSkene{iterations:5,tolerance:0.05},&q2.qs,&q2.rd,1);
+        assert_eq!(e5,TruthEstimate{ans:0,confidence:0.});
+    }
+
+    #[test]
+    fn test__QStruct_truth_estimator() {
+        let mut q = truth_estimator__test_case1();
+        assert_eq!(q.ans_to_q(1),-44);
+
+        q.truth_est = TruthEstimator::DawidSkene{iterations:20,tolerance:0.05};
+        assert_eq!(q.ans_to_q(1),40);
+        assert!(q.ans_confidence(1) > 0.9);
+        assert_eq!(q.ans_confidence(0),1.);
+
+        // contradiction of a new answer is against the configured estimate
+        q.response_to_nodeset(0,vec![0].into_iter().collect(),1,40);
+        assert_eq!(q.rd.x[Dim((0,1))],0.);
+    }
+}